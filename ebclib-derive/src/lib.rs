@@ -0,0 +1,143 @@
+//! Derive macros for `ebclib`.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitChar, Token, parse_macro_input, punctuated::Punctuated};
+
+use ebclib_quest::QuestArgs;
+
+/// Map the unit variants of an enum to the characters given by `#[symbol('x')]`.
+///
+/// Generates `TryFrom<char>` (with a `String` error), `From<Enum> for char`, `Display`
+/// and an `ALL` slice of every variant in order. A variant can list several characters,
+/// e.g. `#[symbol('.', 'S')]`, and the first is the one it turns back into.
+#[proc_macro_derive(Symbol, attributes(symbol))]
+pub fn derive_symbol(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match symbol(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Mark the item holding a quest's solution, usually its `main`, e.g.
+/// `#[ebclib::quest(event = "koa", year = 2024, number = 7)]`.
+///
+/// The item is left as it is. A runner's build script indexes quest binaries by these
+/// attributes, and a quest binary must be named for the quest it marks. Only the binary
+/// is registered; running it solves every part.
+#[proc_macro_attribute]
+pub fn quest(args: TokenStream, item: TokenStream) -> TokenStream {
+    let item = proc_macro2::TokenStream::from(item);
+    match QuestArgs::parse(args.into()).and_then(|_| check_quest_file()) {
+        Ok(()) => item.into(),
+        Err(e) => {
+            let error = e.to_compile_error();
+            quote! { #error #item }.into()
+        }
+    }
+}
+
+/// Check a quest binary the same way its runner's build script will, so a quest that
+/// couldn't be indexed fails to compile.
+fn check_quest_file() -> syn::Result<()> {
+    let Some(path) = proc_macro::Span::call_site().local_file() else {
+        return Ok(());
+    };
+    if path
+        .parent()
+        .and_then(|dir| dir.file_name())
+        .is_none_or(|dir| dir != "bin")
+    {
+        return Ok(());
+    }
+    let (Some(name), Ok(source)) = (
+        path.file_stem().and_then(|name| name.to_str()),
+        std::fs::read_to_string(&path),
+    ) else {
+        return Ok(());
+    };
+    ebclib_quest::in_file(name, &source)
+        .map(|_| ())
+        .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), e))
+}
+
+fn symbol(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "Symbol can only be derived for enums",
+        ));
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut variants = vec![];
+    let mut parse_arms = vec![];
+    let mut char_arms = vec![];
+    let mut seen: Vec<LitChar> = vec![];
+    for variant in data.variants.iter() {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "Symbol variants can't have fields",
+            ));
+        }
+        let mut chars = vec![];
+        for attr in variant.attrs.iter().filter(|a| a.path().is_ident("symbol")) {
+            chars.extend(attr.parse_args_with(Punctuated::<LitChar, Token![,]>::parse_terminated)?);
+        }
+        let Some(first) = chars.first() else {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "Missing #[symbol('x')] on variant",
+            ));
+        };
+        for ch in chars.iter() {
+            if seen.iter().any(|s| s.value() == ch.value()) {
+                return Err(syn::Error::new_spanned(ch, "Symbol used more than once"));
+            }
+            seen.push(ch.clone());
+        }
+        let ident = &variant.ident;
+        variants.push(quote! { Self::#ident });
+        parse_arms.push(quote! { #(#chars)|* => Ok(Self::#ident), });
+        char_arms.push(quote! { #name::#ident => #first, });
+    }
+
+    Ok(quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Every variant in declaration order.
+            pub const ALL: &'static [Self] = &[#(#variants),*];
+        }
+
+        impl #impl_generics ::core::convert::TryFrom<char> for #name #ty_generics #where_clause {
+            type Error = ::std::string::String;
+
+            fn try_from(value: char) -> ::core::result::Result<Self, Self::Error> {
+                match value {
+                    #(#parse_arms)*
+                    _ => Err(::std::format!("Unknown char {value:?}")),
+                }
+            }
+        }
+
+        impl #impl_generics ::core::convert::From<#name #ty_generics> for char #where_clause {
+            fn from(value: #name #ty_generics) -> char {
+                match value {
+                    #(#char_arms)*
+                }
+            }
+        }
+
+        impl #impl_generics ::core::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                let ch: char = match self {
+                    #(#char_arms)*
+                };
+                ::core::fmt::Write::write_char(f, ch)
+            }
+        }
+    })
+}
@@ -0,0 +1,153 @@
+//! The arguments of `#[ebclib::quest(...)]` and finding the attribute in a quest's
+//! source. Both the attribute and runner build scripts use this, so they agree on what
+//! a quest looks like.
+
+use syn::{Attribute, Item, LitInt, LitStr, Meta, meta};
+
+/// A quest's `event = "koa", year = 2024, number = 7`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuestArgs {
+    pub event: String,
+    pub year: u16,
+    pub number: u8,
+}
+
+impl QuestArgs {
+    /// Parse and check the arguments inside the attribute's parentheses.
+    pub fn parse(tokens: proc_macro2::TokenStream) -> syn::Result<Self> {
+        let mut event: Option<LitStr> = None;
+        let mut year: Option<LitInt> = None;
+        let mut number: Option<LitInt> = None;
+        let parser = meta::parser(|meta| {
+            if meta.path.is_ident("event") {
+                event = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("year") {
+                year = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("number") {
+                number = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("Expected event, year or number"));
+            }
+            Ok(())
+        });
+        syn::parse::Parser::parse2(parser, tokens)?;
+        let missing =
+            |name| syn::Error::new(proc_macro2::Span::call_site(), format!("Missing {name}"));
+        let event = event.ok_or_else(|| missing("event"))?;
+        let year = year.ok_or_else(|| missing("year"))?;
+        let number = number.ok_or_else(|| missing("number"))?;
+        if event.value().is_empty() || !event.value().chars().all(|c| c.is_ascii_lowercase()) {
+            return Err(syn::Error::new_spanned(
+                event,
+                "Event must be lowercase letters",
+            ));
+        }
+        let number_value = number.base10_parse::<u8>()?;
+        if !(1..=99).contains(&number_value) {
+            return Err(syn::Error::new_spanned(
+                number,
+                "Quest number must be 1 to 99",
+            ));
+        }
+        Ok(Self {
+            event: event.value(),
+            year: year.base10_parse()?,
+            number: number_value,
+        })
+    }
+
+    /// Name of the binary holding the quest, e.g. `koa2024q07`.
+    pub fn bin(&self) -> String {
+        format!("{}{}q{:02}", self.event, self.year, self.number)
+    }
+}
+
+/// Whether `attr` is `#[quest(...)]` or `#[ebclib::quest(...)]`.
+fn is_quest(attr: &Attribute) -> bool {
+    let path = attr.path();
+    path.is_ident("quest")
+        || (path.segments.len() == 2
+            && path.segments[0].ident == "ebclib"
+            && path.segments[1].ident == "quest")
+}
+
+/// The arguments of the one quest attribute on a function, module or impl block in the
+/// source of the binary called `name`, which must match them.
+pub fn in_file(name: &str, source: &str) -> Result<QuestArgs, String> {
+    let file = syn::parse_file(source).map_err(|e| format!("Can't parse quest: {e}"))?;
+    let mut attrs = file
+        .items
+        .iter()
+        .flat_map(|item| match item {
+            Item::Fn(item) => item.attrs.as_slice(),
+            Item::Mod(item) => item.attrs.as_slice(),
+            Item::Impl(item) => item.attrs.as_slice(),
+            _ => &[],
+        })
+        .filter(|attr| is_quest(attr));
+    let attr = attrs
+        .next()
+        .ok_or("Missing #[ebclib::quest(...)] attribute")?;
+    if attrs.next().is_some() {
+        return Err("More than one quest attribute".to_string());
+    }
+    let Meta::List(list) = &attr.meta else {
+        return Err("Quest attribute needs event, year and number".to_string());
+    };
+    let args = QuestArgs::parse(list.tokens.clone()).map_err(|e| e.to_string())?;
+    if args.bin() != name {
+        return Err(format!("Quest attribute says {}", args.bin()));
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_file() {
+        let source = r#"
+use std::fs;
+
+#[ebclib::quest(
+    event = "koa",
+    year = 2024,
+    number = 0x7, // the racing one
+)]
+fn main() {}
+"#;
+        let args = in_file("koa2024q07", source).unwrap();
+        assert_eq!(
+            QuestArgs {
+                event: "koa".to_string(),
+                year: 2024,
+                number: 7
+            },
+            args
+        );
+        assert_eq!(
+            Err("Quest attribute says koa2024q07".to_string()),
+            in_file("koa2024q08", source)
+        );
+    }
+
+    #[test]
+    fn test_in_file_errors() {
+        assert_eq!(
+            Err("Missing #[ebclib::quest(...)] attribute".to_string()),
+            in_file("koa2024q01", "fn main() {}")
+        );
+        let twice = "#[quest(event = \"koa\", year = 2024, number = 1)] mod a {}\n\
+                     #[quest(event = \"koa\", year = 2024, number = 1)] impl A {}";
+        assert_eq!(
+            Err("More than one quest attribute".to_string()),
+            in_file("koa2024q01", twice)
+        );
+        let bad = "#[ebclib::quest(event = \"KOA\", year = 2024, number = 1)] fn main() {}";
+        assert_eq!(
+            Err("Event must be lowercase letters".to_string()),
+            in_file("koa2024q01", bad)
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//! Catapult shots that rise, fly flat and then fall, meeting meteors in discrete time.
+//!
+//! A shot with power `p` leaves a catapult at `(0, catapult)` and moves one column right
+//! every step: up for `p` steps, level for `p` steps, then down until it goes below 0.
+
+/// A position as `(x, y)`.
+pub type Point = (i64, i64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shot {
+    pub catapult: i64,
+    pub power: i64,
+}
+
+impl Shot {
+    pub fn new(catapult: i64, power: i64) -> Self {
+        Self { catapult, power }
+    }
+
+    /// Where the shot is after `step` steps, ignoring the ground.
+    pub fn position(&self, step: i64) -> Point {
+        let p = self.power;
+        let rise = if step <= p {
+            step
+        } else if step <= 2 * p {
+            p
+        } else {
+            3 * p - step
+        };
+        (step, self.catapult + rise)
+    }
+
+    /// Every position from step 1 until the shot hits the ground, one move at a time.
+    pub fn trajectory(&self) -> impl Iterator<Item = Point> + use<> {
+        let power = self.power.max(0) as usize;
+        std::iter::repeat_n(1, power)
+            .chain(std::iter::repeat_n(0, power))
+            .chain(std::iter::repeat(-1))
+            .scan((0, self.catapult), |pos, dy| {
+                *pos = (pos.0 + 1, pos.1 + dy);
+                Some(*pos)
+            })
+            .take_while(|pos| pos.1 >= 0)
+    }
+}
+
+/// Something moving `(dx, dy)` every step, left and down or not at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Meteor {
+    pub x: i64,
+    pub y: i64,
+    pub dx: i64,
+    pub dy: i64,
+}
+
+impl Meteor {
+    /// A meteor falling diagonally towards the catapults.
+    pub fn falling(x: i64, y: i64) -> Self {
+        Self {
+            x,
+            y,
+            dx: -1,
+            dy: -1,
+        }
+    }
+
+    /// A target that doesn't move.
+    pub fn still(x: i64, y: i64) -> Self {
+        Self { x, y, dx: 0, dy: 0 }
+    }
+
+    pub fn position(&self, time: i64) -> Point {
+        (self.x + self.dx * time, self.y + self.dy * time)
+    }
+
+    /// The same meteor `time` steps later.
+    pub fn after(&self, time: i64) -> Self {
+        let (x, y) = self.position(time);
+        Self { x, y, ..*self }
+    }
+
+    /// Every position from step 1 while it's above ground, one move at a time.
+    pub fn path(&self) -> impl Iterator<Item = Point> + use<> {
+        let (dx, dy) = (self.dx, self.dy);
+        std::iter::successors(Some((self.x, self.y)), move |pos| {
+            Some((pos.0 + dx, pos.1 + dy))
+        })
+        .skip(1)
+        .take_while(|pos| pos.1 >= 0)
+    }
+
+    /// The only step and place a shot fired now could meet it, if they share a column
+    /// on a whole step. Panics if it moves up or right.
+    pub fn meeting_point(&self) -> Option<(i64, Point)> {
+        assert!(
+            self.dx <= 0 && self.dy <= 0,
+            "Meteor must move left and down"
+        );
+        // The shot gains one column a step on the meteor.
+        let closing = 1 - self.dx;
+        if self.x < 1 || self.x % closing != 0 {
+            return None;
+        }
+        let step = self.x / closing;
+        Some((step, self.position(step)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hit {
+    pub step: i64,
+    pub x: i64,
+    pub y: i64,
+}
+
+/// Where a shot fired now with `power` first meets `meteor`, worked out directly.
+pub fn first_intersection(catapult: i64, power: i64, meteor: &Meteor) -> Option<Hit> {
+    let (step, (x, y)) = meteor.meeting_point()?;
+    let shot = Shot::new(catapult, power);
+    (y >= 0 && shot.position(step) == (x, y)).then_some(Hit { step, x, y })
+}
+
+/// The shortest wait before firing from `catapult` that lets a shot hit `meteor`, the
+/// lowest power that does it, and where they meet. Waiting longer only meets it lower.
+pub fn earliest_hit(catapult: i64, meteor: &Meteor) -> Option<(i64, i64, Hit)> {
+    let moving = (meteor.dx, meteor.dy) != (0, 0);
+    (0..)
+        .map(|delay| (delay, meteor.after(delay)))
+        .take_while(|(delay, later)| (*delay == 0 || moving) && later.x >= 1 && later.y >= 0)
+        .find_map(|(delay, later)| {
+            let (_, target) = later.meeting_point()?;
+            let power = lowest_power(catapult, target)?;
+            let hit = first_intersection(catapult, power, &later)?;
+            Some((delay, power, hit))
+        })
+}
+
+/// [`first_intersection`] found by moving both a step at a time.
+pub fn simulate_intersection(catapult: i64, power: i64, meteor: &Meteor) -> Option<Hit> {
+    Shot::new(catapult, power)
+        .trajectory()
+        .zip(meteor.path())
+        .zip(1..)
+        .find(|((shot, rock), _)| shot == rock)
+        .map(|(((x, y), _), step)| Hit { step, x, y })
+}
+
+/// Smallest power that passes through `target`. A target level with the catapult's
+/// diagonal is hit by every power from its column up.
+pub fn lowest_power(catapult: i64, target: Point) -> Option<i64> {
+    let (x, y) = target;
+    if x < 1 || y < 0 {
+        return None;
+    }
+    let rise = y - catapult;
+    if rise == x {
+        // On the way up.
+        Some(x)
+    } else if rise < x && 2 * rise >= x {
+        // In the level stretch.
+        Some(rise)
+    } else if 2 * rise < x && (x + rise) % 3 == 0 && x + rise >= 3 {
+        // On the way down.
+        Some((x + rise) / 3)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Xorshift, enough to spread test cases about.
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: i64) -> i64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as i64
+        }
+    }
+
+    #[test]
+    fn test_trajectory() {
+        let path = Shot::new(1, 2).trajectory().collect::<Vec<_>>();
+        let expected = vec![(1, 2), (2, 3), (3, 3), (4, 3), (5, 2), (6, 1), (7, 0)];
+        assert_eq!(expected, path);
+        assert!(
+            path.iter()
+                .all(|pos| *pos == Shot::new(1, 2).position(pos.0))
+        );
+    }
+
+    #[test]
+    fn test_examples() {
+        // Meteor at (6, 5) is hit by A with power 2, and waiting a step misses.
+        let meteor = Meteor::falling(6, 5);
+        let hit = first_intersection(0, 2, &meteor);
+        assert_eq!(
+            Some(Hit {
+                step: 3,
+                x: 3,
+                y: 2
+            }),
+            hit
+        );
+        assert_eq!(None, first_intersection(1, 1, &meteor));
+        assert_eq!(None, first_intersection(0, 2, &meteor.after(1)));
+        assert_eq!(Some((0, 2, hit.unwrap())), earliest_hit(0, &meteor));
+        // An odd column can't be met on a whole step until the meteor has moved once.
+        let (delay, _, hit) = earliest_hit(0, &Meteor::falling(7, 6)).unwrap();
+        assert_eq!((1, 3), (delay, hit.step));
+        assert_eq!(None, earliest_hit(0, &Meteor::still(7, 1)));
+        assert_eq!(Some(2), lowest_power(2, (7, 1)));
+        assert_eq!(Some(2), lowest_power(1, (7, 0)));
+        assert_eq!(None, lowest_power(0, (7, 1)));
+    }
+
+    #[test]
+    fn test_closed_form_matches_simulation() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..20_000 {
+            let catapult = rng.below(3);
+            let power = rng.below(20) + 1;
+            let meteor = Meteor {
+                x: rng.below(80),
+                y: rng.below(60),
+                dx: -rng.below(3),
+                dy: -rng.below(2),
+            };
+            assert_eq!(
+                simulate_intersection(catapult, power, &meteor),
+                first_intersection(catapult, power, &meteor),
+                "{catapult} {power} {meteor:?}"
+            );
+        }
+        for _ in 0..5_000 {
+            let catapult = rng.below(3);
+            let target = (rng.below(40), rng.below(30));
+            let brute = (1..=target.0).find(|power| {
+                let meteor = Meteor::still(target.0, target.1);
+                simulate_intersection(catapult, *power, &meteor).is_some()
+            });
+            assert_eq!(
+                brute,
+                lowest_power(catapult, target),
+                "{catapult} {target:?}"
+            );
+        }
+    }
+}
@@ -31,7 +31,9 @@ fn main() {
 }
 
 fn create_quest(year: i32, quest: u32, triple: &str) -> io::Result<String> {
-    let bin = PathBuf::from(format!("{triple}{year}/src/bin/ebc{year}q{quest:02}.rs"));
+    let bin = PathBuf::from(format!(
+        "{triple}{year}/src/bin/{triple}{year}q{quest:02}.rs"
+    ));
     if bin.exists() {
         return Ok(format!("{year} quest {quest} already exists. Skipping."));
     }
@@ -42,7 +44,8 @@ fn create_quest(year: i32, quest: u32, triple: &str) -> io::Result<String> {
     }
     let template = format!(
         r#"use puzlib::read_lines;
-    
+
+#[ebclib::quest(event = "{triple}", year = {year}, number = {quest})]
 fn main() {{
     let _input = puzlib::FileReader::new("{triple}{year}/inputs/everbody_codes_e{year}_q{quest:02}_p1.txt");
     println!("Part 1: {{}}", part_one());
@@ -0,0 +1,240 @@
+//! Small sets of symbols stored as bits, cheap enough to copy and hash inside search states.
+
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign},
+};
+
+use crate::intern::Interner;
+
+/// Gives each symbol a bit so sets of them can be built as [`SymbolSet`]s.
+#[derive(Debug, Clone)]
+pub struct Alphabet<T> {
+    symbols: Interner<T>,
+}
+
+impl<T> Default for Alphabet<T> {
+    fn default() -> Self {
+        Self {
+            symbols: Interner::default(),
+        }
+    }
+}
+
+impl<T: Hash + Eq + Clone> Alphabet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bit for `symbol`, adding it to the alphabet if needed.
+    pub fn intern(&mut self, symbol: T) -> usize {
+        self.symbols.intern(symbol)
+    }
+
+    /// Bit for `symbol` if it is in the alphabet.
+    pub fn bit(&self, symbol: &T) -> Option<usize> {
+        self.symbols.get(symbol)
+    }
+
+    pub fn symbol(&self, bit: usize) -> &T {
+        self.symbols.resolve(bit)
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Set holding every symbol in the alphabet.
+    pub fn full<const W: usize>(&self) -> SymbolSet<W> {
+        (0..self.len()).collect()
+    }
+
+    /// Set holding `symbols`, adding any new ones to the alphabet.
+    pub fn set<const W: usize>(&mut self, symbols: impl IntoIterator<Item = T>) -> SymbolSet<W> {
+        symbols.into_iter().map(|s| self.intern(s)).collect()
+    }
+
+    /// The symbols in `set`, in bit order.
+    pub fn symbols<const W: usize>(&self, set: &SymbolSet<W>) -> Vec<&T> {
+        set.iter().map(|bit| self.symbol(bit)).collect()
+    }
+}
+
+/// A set of bits `0..64 * W`. One word is enough for most alphabets; more can be asked
+/// for with `W` when an alphabet is larger.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SymbolSet<const W: usize = 1> {
+    words: [u64; W],
+}
+
+impl<const W: usize> Default for SymbolSet<W> {
+    fn default() -> Self {
+        Self { words: [0; W] }
+    }
+}
+
+impl<const W: usize> SymbolSet<W> {
+    /// Number of symbols the set can hold.
+    pub const CAPACITY: usize = 64 * W;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set holding just `bit`.
+    pub fn single(bit: usize) -> Self {
+        let mut set = Self::new();
+        set.insert(bit);
+        set
+    }
+
+    /// Add `bit`, returning false if it was already there. Panics if it is past
+    /// [`SymbolSet::CAPACITY`].
+    pub fn insert(&mut self, bit: usize) -> bool {
+        assert!(bit < Self::CAPACITY, "Symbol {bit} doesn't fit in the set");
+        let had = self.contains(bit);
+        self.words[bit / 64] |= 1 << (bit % 64);
+        !had
+    }
+
+    /// Remove `bit`, returning true if it was there.
+    pub fn remove(&mut self, bit: usize) -> bool {
+        let had = self.contains(bit);
+        if had {
+            self.words[bit / 64] &= !(1 << (bit % 64));
+        }
+        had
+    }
+
+    pub fn contains(&self, bit: usize) -> bool {
+        bit < Self::CAPACITY && self.words[bit / 64] & (1 << (bit % 64)) != 0
+    }
+
+    /// Number of symbols in the set.
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        *self | *other
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        *self & *other
+    }
+
+    /// True when every symbol in `other` is also in this set.
+    pub fn is_superset(&self, other: &Self) -> bool {
+        self.words.iter().zip(other.words).all(|(a, b)| a & b == b)
+    }
+
+    /// The bits in the set, lowest first.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..Self::CAPACITY).filter(|bit| self.contains(*bit))
+    }
+}
+
+impl<const W: usize> Debug for SymbolSet<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<const W: usize> FromIterator<usize> for SymbolSet<W> {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        iter.into_iter().for_each(|bit| {
+            set.insert(bit);
+        });
+        set
+    }
+}
+
+impl<const W: usize> BitOr for SymbolSet<W> {
+    type Output = Self;
+
+    fn bitor(mut self, rhs: Self) -> Self::Output {
+        self |= rhs;
+        self
+    }
+}
+
+impl<const W: usize> BitOrAssign for SymbolSet<W> {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.words
+            .iter_mut()
+            .zip(rhs.words)
+            .for_each(|(a, b)| *a |= b);
+    }
+}
+
+impl<const W: usize> BitAnd for SymbolSet<W> {
+    type Output = Self;
+
+    fn bitand(mut self, rhs: Self) -> Self::Output {
+        self &= rhs;
+        self
+    }
+}
+
+impl<const W: usize> BitAndAssign for SymbolSet<W> {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.words
+            .iter_mut()
+            .zip(rhs.words)
+            .for_each(|(a, b)| *a &= b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alphabet() {
+        let mut alphabet = Alphabet::new();
+        let herbs: SymbolSet = alphabet.set("AbcA".chars());
+        assert_eq!(3, herbs.len());
+        assert_eq!(vec![&'A', &'b', &'c'], alphabet.symbols(&herbs));
+        assert_eq!(herbs, alphabet.full());
+        assert_eq!(Some(1), alphabet.bit(&'b'));
+    }
+
+    #[test]
+    fn test_set_ops() {
+        let a: SymbolSet = [0, 3, 5].into_iter().collect();
+        let b: SymbolSet = [3, 7].into_iter().collect();
+        assert_eq!(vec![0, 3, 5, 7], a.union(&b).iter().collect::<Vec<_>>());
+        assert_eq!(SymbolSet::single(3), a & b);
+        assert!((a | b).is_superset(&b));
+        assert!(!a.is_superset(&b));
+    }
+
+    #[test]
+    fn test_wide_sets() {
+        let mut alphabet = Alphabet::new();
+        let names = (0..100).map(|n| format!("herb{n}"));
+        let mut set: SymbolSet<2> = alphabet.set(names);
+        assert_eq!(100, set.len());
+        assert!(set.contains(99));
+        assert!(set.remove(99));
+        assert!(!set.remove(99));
+        assert!(!set.contains(99));
+        assert_eq!(99, set.len());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_overflow() {
+        SymbolSet::<1>::new().insert(64);
+    }
+}
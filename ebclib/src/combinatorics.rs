@@ -0,0 +1,233 @@
+//! Counting and enumerating arrangements without visiting duplicates.
+
+use crate::par;
+
+/// Number of distinct orders of a multiset with the given counts of each value, i.e.
+/// `(sum of counts)! / (count₁! count₂! ...)`. Panics if it doesn't fit in a `u128`.
+pub fn multinomial(counts: &[usize]) -> u128 {
+    let mut res: u128 = 1;
+    let mut total: u128 = 0;
+    for count in counts {
+        // Multiplying in C(total + i, i) one step at a time keeps every division exact.
+        for i in 1..=*count as u128 {
+            total += 1;
+            res = res.checked_mul(total).expect("Multinomial overflowed") / i;
+        }
+    }
+    res
+}
+
+/// Ways of choosing `k` items from `n`.
+pub fn binomial(n: usize, k: usize) -> u128 {
+    if k > n {
+        return 0;
+    }
+    multinomial(&[k, n - k])
+}
+
+/// Rearrange `items` into the next larger order, returning false (and leaving them
+/// sorted) once they were already the largest. Equal items are never swapped with each
+/// other, so repeats are skipped.
+pub fn next_permutation<T: Ord>(items: &mut [T]) -> bool {
+    let Some(pivot) = (1..items.len()).rev().find(|i| items[i - 1] < items[*i]) else {
+        items.reverse();
+        return false;
+    };
+    let swap = (pivot..items.len())
+        .rev()
+        .find(|i| items[*i] > items[pivot - 1])
+        .unwrap();
+    items.swap(pivot - 1, swap);
+    items[pivot..].reverse();
+    true
+}
+
+/// Every distinct order of a multiset in lexicographic order.
+#[derive(Debug, Clone)]
+pub struct MultisetPermutations<T> {
+    current: Vec<T>,
+    done: bool,
+}
+
+impl<T: Ord + Clone> MultisetPermutations<T> {
+    pub fn new(items: &[T]) -> Self {
+        Self::starting_at(items, 0)
+    }
+
+    /// Skip straight to the `rank`th order without visiting those before it.
+    pub fn starting_at(items: &[T], rank: u128) -> Self {
+        let mut remaining = items.to_vec();
+        remaining.sort();
+        let total = Self::count(items);
+        if rank >= total {
+            return Self {
+                current: remaining,
+                done: true,
+            };
+        }
+        let mut rank = rank;
+        let mut current = Vec::with_capacity(items.len());
+        while !remaining.is_empty() {
+            let mut idx = 0;
+            loop {
+                // Orders that start with this value, given what is left.
+                let mut rest = remaining.clone();
+                rest.remove(idx);
+                let starting_here = Self::count(&rest);
+                if rank < starting_here {
+                    break;
+                }
+                rank -= starting_here;
+                let value = &remaining[idx];
+                idx += remaining[idx..].iter().take_while(|v| *v == value).count();
+            }
+            current.push(remaining.remove(idx));
+        }
+        Self {
+            current,
+            done: false,
+        }
+    }
+
+    /// Number of distinct orders of `items`.
+    pub fn count(items: &[T]) -> u128 {
+        let mut sorted = items.iter().collect::<Vec<_>>();
+        sorted.sort();
+        let counts = sorted
+            .chunk_by(|a, b| a == b)
+            .map(|run| run.len())
+            .collect::<Vec<_>>();
+        multinomial(&counts)
+    }
+
+    /// Split every order into `parts` runs of about the same length that can be
+    /// walked independently. Panics if a run would be too long to count in a `usize`.
+    pub fn chunks(items: &[T], parts: usize) -> Vec<std::iter::Take<Self>> {
+        let total = Self::count(items);
+        let size = total.div_ceil(parts.max(1) as u128).max(1);
+        let take = usize::try_from(size)
+            .unwrap_or_else(|_| panic!("Runs of {size} orders are too long to walk"));
+        (0..parts.max(1) as u128)
+            .map(|part| Self::starting_at(items, part * size).take(take))
+            .collect()
+    }
+}
+
+impl<T: Ord + Clone> Iterator for MultisetPermutations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let res = self.current.clone();
+        self.done = !next_permutation(&mut self.current);
+        Some(res)
+    }
+}
+
+/// Sum `score` over every distinct order of `items`, with a run of orders for each of
+/// the [`par::workers`].
+pub fn par_sum_permutations<T>(items: &[T], score: impl Fn(&[T]) -> u64 + Sync) -> u64
+where
+    T: Ord + Clone + Sync,
+{
+    let chunks = MultisetPermutations::chunks(items, par::workers());
+    par::par_sum(&chunks, |chunk| {
+        chunk.clone().map(|order| score(&order)).sum::<u64>()
+    })
+}
+
+/// Every way of picking `k` items, keeping their order, in lexicographic order of
+/// positions.
+#[derive(Debug, Clone)]
+pub struct Combinations<'a, T> {
+    items: &'a [T],
+    picks: Vec<usize>,
+    done: bool,
+}
+
+pub fn combinations<T>(items: &[T], k: usize) -> Combinations<'_, T> {
+    Combinations {
+        items,
+        picks: (0..k).collect(),
+        done: k > items.len(),
+    }
+}
+
+impl<T: Clone> Iterator for Combinations<'_, T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let res = self.picks.iter().map(|i| self.items[*i].clone()).collect();
+        let (n, k) = (self.items.len(), self.picks.len());
+        match (0..k).rev().find(|i| self.picks[*i] < n - k + i) {
+            Some(i) => {
+                self.picks[i] += 1;
+                for j in i + 1..k {
+                    self.picks[j] = self.picks[j - 1] + 1;
+                }
+            }
+            None => self.done = true,
+        }
+        Some(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts() {
+        assert_eq!(9240, multinomial(&[5, 3, 3]));
+        assert_eq!(1, multinomial(&[]));
+        assert_eq!(10, binomial(5, 2));
+        assert_eq!(0, binomial(2, 5));
+        let plan = "+++++---===".chars().collect::<Vec<_>>();
+        assert_eq!(9240, MultisetPermutations::count(&plan));
+        assert_eq!(9240, MultisetPermutations::new(&plan).count());
+    }
+
+    #[test]
+    fn test_order() {
+        let orders = MultisetPermutations::new(&['b', 'a', 'a'])
+            .map(|p| p.into_iter().collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["aab", "aba", "baa"], orders);
+    }
+
+    #[test]
+    fn test_chunks_cover_everything() {
+        let items = [1, 1, 2, 3, 3, 3];
+        let all = MultisetPermutations::new(&items).collect::<Vec<_>>();
+        for parts in [1, 3, 7, 100] {
+            let chunked = MultisetPermutations::chunks(&items, parts)
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+            assert_eq!(all, chunked);
+        }
+        let sum = par_sum_permutations(&items, |p| p[0] as u64);
+        assert_eq!(all.iter().map(|p| p[0] as u64).sum::<u64>(), sum);
+    }
+
+    #[test]
+    #[should_panic(expected = "too long to walk")]
+    fn test_chunks_too_long() {
+        MultisetPermutations::chunks(&(0..30).collect::<Vec<_>>(), 1);
+    }
+
+    #[test]
+    fn test_combinations() {
+        let picks = combinations(&['a', 'b', 'c', 'd'], 2)
+            .map(|p| p.into_iter().collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["ab", "ac", "ad", "bc", "bd", "cd"], picks);
+        assert_eq!(1, combinations(&[1, 2], 0).count());
+        assert_eq!(0, combinations(&[1, 2], 3).count());
+    }
+}
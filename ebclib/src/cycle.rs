@@ -0,0 +1,231 @@
+//! Cycle detection for deterministic simulations so huge step counts only need
+//! one pass over the repeating part.
+
+use std::{collections::HashMap, hash::Hash};
+
+/// The shape of a sequence `s0, s1 = step(s0), ...`. The first `prefix` states are
+/// never repeated and from then on the states repeat every `period` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub prefix: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// Number of distinct states in the sequence.
+    pub fn len(&self) -> usize {
+        self.prefix + self.period
+    }
+
+    /// Always false, every sequence has at least its starting state.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Index of the state in the first `len()` states that matches step `n`.
+    pub fn index(&self, n: u64) -> usize {
+        let prefix = self.prefix as u64;
+        if n < prefix {
+            n as usize
+        } else {
+            (prefix + (n - prefix) % self.period as u64) as usize
+        }
+    }
+}
+
+/// Brent's algorithm. Only keeps two states in memory but steps the sequence a few
+/// more times than [`detect`].
+pub fn brent<S: Clone + Eq>(start: &S, step: impl Fn(&S) -> S) -> Cycle {
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(start);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    // Start the hare a period ahead so they meet where the cycle starts.
+    let mut tortoise = start.clone();
+    let mut hare = start.clone();
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    let mut prefix = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+    Cycle { prefix, period }
+}
+
+/// Remembers every state it has seen until one comes around again.
+#[derive(Debug, Clone)]
+pub struct Detector<S> {
+    seen: HashMap<S, usize>,
+}
+
+impl<S> Default for Detector<S> {
+    fn default() -> Self {
+        Self {
+            seen: HashMap::new(),
+        }
+    }
+}
+
+impl<S: Hash + Eq> Detector<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the next state in the sequence. Returns the cycle once a state repeats.
+    pub fn observe(&mut self, state: S) -> Option<Cycle> {
+        let next = self.seen.len();
+        match self.seen.get(&state) {
+            Some(first) => Some(Cycle {
+                prefix: *first,
+                period: next - first,
+            }),
+            None => {
+                self.seen.insert(state, next);
+                None
+            }
+        }
+    }
+}
+
+/// Find the cycle by hashing every state. Fewer calls to `step` than [`brent`]
+/// at the cost of holding every state in memory.
+pub fn detect<S: Clone + Hash + Eq>(start: &S, step: impl Fn(&S) -> S) -> Cycle {
+    let mut detector = Detector::new();
+    let mut state = start.clone();
+    loop {
+        let next = step(&state);
+        if let Some(cycle) = detector.observe(state) {
+            return cycle;
+        }
+        state = next;
+    }
+}
+
+/// The state after `n` steps.
+pub fn state_at<S: Clone + Eq>(start: &S, step: impl Fn(&S) -> S, n: u64) -> S {
+    let cycle = brent(start, &step);
+    let mut state = start.clone();
+    for _ in 0..cycle.index(n) {
+        state = step(&state);
+    }
+    state
+}
+
+/// Sum of `score` for the state after each of the first `n` steps. The starting
+/// state itself isn't scored.
+pub fn sum_over<S: Clone + Eq>(
+    start: &S,
+    step: impl Fn(&S) -> S,
+    score: impl Fn(&S) -> u64,
+    n: u64,
+) -> u64 {
+    let cycle = brent(start, &step);
+    let mut state = start.clone();
+    let mut scores = Vec::with_capacity(cycle.len());
+    scores.push(score(&state));
+    for _ in 1..cycle.len() {
+        state = step(&state);
+        scores.push(score(&state));
+    }
+    let distinct = cycle.len() as u64;
+    if n < distinct {
+        return scores[1..=n as usize].iter().sum();
+    }
+    // Everything from `distinct` on is whole loops of the cycle plus a remainder.
+    let head = scores[1..].iter().sum::<u64>();
+    let looped = &scores[cycle.prefix..];
+    let count = n - distinct + 1;
+    let full = count / cycle.period as u64;
+    let rem = (count % cycle.period as u64) as usize;
+    head + full * looped.iter().sum::<u64>() + looped[..rem].iter().sum::<u64>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2, 4, 16, 256 % 1000 ... enters a loop after a few steps.
+    fn square(v: &u64) -> u64 {
+        v * v % 1000
+    }
+
+    fn brute_force(start: u64, n: u64) -> Vec<u64> {
+        let mut states = vec![start];
+        for _ in 0..n {
+            states.push(square(states.last().unwrap()));
+        }
+        states
+    }
+
+    #[test]
+    fn test_brent_matches_detect() {
+        for start in 2..50 {
+            assert_eq!(detect(&start, square), brent(&start, square));
+        }
+    }
+
+    #[test]
+    fn test_cycle_shape() {
+        let cycle = detect(&0_u64, |v| (v + 1) % 5);
+        assert_eq!(
+            Cycle {
+                prefix: 0,
+                period: 5
+            },
+            cycle
+        );
+        let cycle = detect(&0_u64, |v| if *v < 3 { v + 1 } else { 3 + (v - 2) % 4 });
+        assert_eq!(
+            Cycle {
+                prefix: 3,
+                period: 4
+            },
+            cycle
+        );
+    }
+
+    #[test]
+    fn test_state_at() {
+        let states = brute_force(7, 200);
+        for n in 0..200 {
+            assert_eq!(states[n as usize], state_at(&7, square, n));
+        }
+    }
+
+    #[test]
+    fn test_sum_over() {
+        let states = brute_force(3, 300);
+        for n in 0..300 {
+            let expected = states[1..=n as usize].iter().sum::<u64>();
+            assert_eq!(expected, sum_over(&3, square, |v| *v, n));
+        }
+    }
+
+    #[test]
+    fn test_detector() {
+        let mut detector = Detector::new();
+        assert_eq!(None, detector.observe('a'));
+        assert_eq!(None, detector.observe('b'));
+        assert_eq!(None, detector.observe('c'));
+        assert_eq!(
+            Some(Cycle {
+                prefix: 1,
+                period: 2
+            }),
+            detector.observe('b')
+        );
+    }
+}
@@ -0,0 +1,5 @@
+//! Dynamic programming helpers shared between quests.
+
+pub mod coin_change;
+pub mod frontier;
+pub mod lattice;
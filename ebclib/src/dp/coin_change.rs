@@ -0,0 +1,233 @@
+//! Unbounded coin change: the fewest coins, the number of ways and an optimal
+//! multiset for every amount up to a limit.
+
+/// Lookup tables for making change from an unlimited supply of each coin.
+///
+/// Only multiples of the gcd of the coins can ever be made, so the tables are
+/// indexed by `amount / gcd` and the impossible amounts in between take no space.
+#[derive(Debug, Clone)]
+pub struct CoinChange {
+    coins: Vec<u64>,
+    step: u64,
+    limit: u64,
+    min: Vec<Option<u64>>,
+    last: Vec<usize>,
+    ways: Vec<u128>,
+}
+
+impl CoinChange {
+    /// Build the tables for every amount in `0..=limit`. Zero valued and
+    /// duplicate coins are ignored.
+    pub fn new(coins: &[u64], limit: u64) -> Self {
+        let mut coins = coins.iter().copied().filter(|c| *c > 0).collect::<Vec<_>>();
+        coins.sort_unstable();
+        coins.dedup();
+        let step = coins.iter().fold(0, |acc, c| gcd(acc, *c)).max(1);
+        let slots = (limit / step) as usize + 1;
+        let mut min = vec![None; slots];
+        let mut last = vec![usize::MAX; slots];
+        let mut ways = vec![0_u128; slots];
+        min[0] = Some(0);
+        ways[0] = 1;
+        for (idx, coin) in coins.iter().enumerate() {
+            let width = (coin / step) as usize;
+            for slot in width..slots {
+                ways[slot] = ways[slot].saturating_add(ways[slot - width]);
+                if let Some(prev) = min[slot - width]
+                    && min[slot].is_none_or(|cur| prev + 1 < cur)
+                {
+                    min[slot] = Some(prev + 1);
+                    last[slot] = idx;
+                }
+            }
+        }
+        Self {
+            coins,
+            step,
+            limit,
+            min,
+            last,
+            ways,
+        }
+    }
+
+    /// The largest amount the tables cover.
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Fewest coins summing to `amount`, or `None` if it can't be made or is past the
+    /// limit.
+    pub fn min_coins(&self, amount: u64) -> Option<u64> {
+        self.slot(amount).and_then(|slot| self.min[slot])
+    }
+
+    /// Number of distinct multisets of coins summing to `amount`, 0 past the limit.
+    /// Saturates at `u128::MAX`.
+    pub fn ways(&self, amount: u64) -> u128 {
+        self.slot(amount).map_or(0, |slot| self.ways[slot])
+    }
+
+    /// One multiset using the fewest coins for `amount`, largest coin first.
+    pub fn reconstruct(&self, amount: u64) -> Option<Vec<u64>> {
+        let mut slot = self.slot(amount)?;
+        self.min[slot]?;
+        let mut used = vec![];
+        while slot > 0 {
+            let coin = self.coins[self.last[slot]];
+            used.push(coin);
+            slot -= (coin / self.step) as usize;
+        }
+        used.sort_unstable_by(|a, b| b.cmp(a));
+        Some(used)
+    }
+
+    /// Fewest coins needed when `amount` is split into `parts` amounts where no two
+    /// differ by more than `spread`, each part being made separately. Parts past the
+    /// limit count as unmakeable.
+    pub fn split(&self, amount: u64, parts: u64, spread: u64) -> Option<u64> {
+        match parts {
+            0 => return (amount == 0).then_some(0),
+            1 => return self.min_coins(amount),
+            _ => {}
+        }
+        // No two parts can differ by more than the whole amount.
+        let spread = spread.min(amount);
+        // Every part lies in `lo..=lo + spread` where `lo` is the smallest part.
+        let highest = amount / parts;
+        let lowest = parts
+            .checked_mul(spread)
+            .map_or(0, |gap| amount.saturating_sub(gap))
+            .div_ceil(parts);
+        (lowest..=highest)
+            .filter_map(|lo| self.split_window(amount - parts * lo, lo, parts, spread))
+            .min()
+    }
+
+    /// Fewest coins for `parts` amounts taken from `lo..=lo + spread`, one of them `lo`
+    /// itself, whose offsets above `lo` sum to `target`.
+    fn split_window(&self, target: u64, lo: u64, parts: u64, spread: u64) -> Option<u64> {
+        // No part can be further above the smallest than the whole of `target`.
+        let spread = spread.min(target);
+        let coins = |offset: u64| self.min_coins(lo + offset);
+        // best[s] is the fewest coins for the parts so far with offsets summing to s,
+        // starting from the smallest part.
+        let mut best = vec![coins(0)];
+        for _ in 2..parts {
+            let len = (best.len() + spread as usize).min(target as usize + 1);
+            let mut next = vec![None; len];
+            for (sum, cost) in best.iter().enumerate() {
+                let Some(cost) = cost else {
+                    continue;
+                };
+                for offset in 0..=(spread as usize).min(len - 1 - sum) {
+                    if let Some(coins) = coins(offset as u64) {
+                        next[sum + offset] = lesser(next[sum + offset], cost + coins);
+                    }
+                }
+            }
+            best = next;
+        }
+        // The last part has to take exactly what is left over.
+        best.iter()
+            .enumerate()
+            .filter_map(|(sum, cost)| {
+                let offset = target.checked_sub(sum as u64).filter(|o| *o <= spread)?;
+                Some((*cost)? + coins(offset)?)
+            })
+            .min()
+    }
+
+    /// Table index of `amount`, or `None` if it's past the limit or can't be made.
+    fn slot(&self, amount: u64) -> Option<usize> {
+        (amount <= self.limit && amount.is_multiple_of(self.step))
+            .then_some((amount / self.step) as usize)
+    }
+}
+
+fn lesser(cur: Option<u64>, value: u64) -> Option<u64> {
+    Some(cur.map_or(value, |cur| cur.min(value)))
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_coins() {
+        let change = CoinChange::new(&[1, 5, 6, 9], 19);
+        assert_eq!(Some(3), change.min_coins(19));
+        assert_eq!(Some(0), change.min_coins(0));
+        assert_eq!(Some(2), change.min_coins(11));
+    }
+
+    #[test]
+    fn test_ways() {
+        let change = CoinChange::new(&[1, 2, 5], 5);
+        assert_eq!(4, change.ways(5));
+        assert_eq!(1, change.ways(0));
+    }
+
+    #[test]
+    fn test_reconstruct() {
+        let change = CoinChange::new(&[1, 3, 5, 10], 16);
+        let coins = change.reconstruct(16).unwrap();
+        assert_eq!(16, coins.iter().sum::<u64>());
+        assert_eq!(change.min_coins(16), Some(coins.len() as u64));
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let change = CoinChange::new(&[4, 6], 20);
+        assert_eq!(None, change.min_coins(7));
+        assert_eq!(None, change.reconstruct(2));
+        assert_eq!(0, change.ways(9));
+        assert_eq!(Some(3), change.min_coins(14));
+    }
+
+    #[test]
+    fn test_split() {
+        let stamps = [
+            1, 3, 5, 10, 15, 16, 20, 24, 25, 30, 37, 38, 49, 50, 74, 75, 100, 101,
+        ];
+        let change = CoinChange::new(&stamps, 546212);
+        let total = [156488, 352486, 546212]
+            .iter()
+            .map(|s| change.split(*s, 2, 100).unwrap())
+            .sum::<u64>();
+        assert_eq!(10449, total);
+    }
+
+    #[test]
+    fn test_split_three_ways() {
+        let change = CoinChange::new(&[1, 10], 30);
+        assert_eq!(Some(3), change.split(30, 3, 0));
+        assert_eq!(Some(4), change.split(31, 3, 1));
+        assert_eq!(None, change.split(31, 3, 0));
+    }
+
+    #[test]
+    fn test_split_at_limit() {
+        let change = CoinChange::new(&[1], 10);
+        assert_eq!(Some(10), change.split(10, 2, 100));
+        assert_eq!(Some(10), change.split(10, 3, u64::MAX));
+        assert_eq!(None, change.split(21, 2, 100));
+        assert_eq!(None, change.min_coins(11));
+        assert_eq!(0, change.ways(11));
+        assert_eq!(None, change.reconstruct(11));
+        assert_eq!(Some(7), change.split(7, 1, 0));
+    }
+
+    #[test]
+    fn test_split_wide_spread() {
+        let change = CoinChange::new(&[1, 3, 5, 10, 15, 16, 20, 24, 25, 30, 37, 38], 546212);
+        // Any split will do, including everything in one part and nothing in the other.
+        let whole = change.min_coins(546212);
+        assert_eq!(whole, change.split(546212, 2, u64::MAX));
+        assert_eq!(change.min_coins(6000), change.split(6000, 3, u64::MAX));
+    }
+}
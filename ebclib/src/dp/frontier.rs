@@ -0,0 +1,343 @@
+//! Best values for every state at one time step, advanced a step at a time.
+
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    hash::{BuildHasher, Hash, RandomState},
+    rc::Rc,
+};
+
+use crate::search::Cost;
+
+/// Which of two values for the same state is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Merge {
+    Max,
+    Min,
+}
+
+impl Merge {
+    /// `Less` when `a` is the better of the two.
+    fn order<V: Ord>(&self, a: &V, b: &V) -> Ordering {
+        match self {
+            Merge::Max => b.cmp(a),
+            Merge::Min => a.cmp(b),
+        }
+    }
+}
+
+type Successors<'a, K, V> = Box<dyn FnMut(&K, V, &mut Vec<(K, V)>) + 'a>;
+type Dominates<'a, K, V> = Box<dyn Fn((&K, V), (&K, V)) -> bool + 'a>;
+/// States within one period, how far along they are and their values, all relative.
+type Shape<K> = Vec<(K, i64, i64)>;
+
+struct Dominance<'a, K, V> {
+    bucket: Box<dyn Fn(&K) -> u64 + 'a>,
+    dominates: Dominates<'a, K, V>,
+}
+
+/// How a frontier repeats: after `steps` steps every state has moved `shift` periods
+/// and every value changed by `change`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    pub start: usize,
+    pub steps: usize,
+    pub shift: i64,
+    pub change: i64,
+}
+
+/// The states alive at the current time, each with the best value reaching it. Only
+/// the states reached by the latest step are kept.
+pub struct Frontier<'a, K, V> {
+    states: HashMap<K, V>,
+    merge: Merge,
+    successors: Successors<'a, K, V>,
+    dominance: Option<Dominance<'a, K, V>>,
+    cap: Option<usize>,
+    time: usize,
+}
+
+impl<'a, K: Hash + Eq + Ord + Clone, V: Cost> Frontier<'a, K, V> {
+    /// `successors` gives the states one step on from a state and their values.
+    pub fn new<I>(
+        starts: impl IntoIterator<Item = (K, V)>,
+        merge: Merge,
+        mut successors: impl FnMut(&K, V) -> I + 'a,
+    ) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut frontier = Self {
+            states: HashMap::new(),
+            merge,
+            successors: Box::new(move |state, value, out| out.extend(successors(state, value))),
+            dominance: None,
+            cap: None,
+            time: 0,
+        };
+        let starts = starts.into_iter().collect::<Vec<_>>();
+        frontier.replace(starts);
+        frontier
+    }
+
+    /// After each step drop a state when another with the same `key` `dominates` it,
+    /// i.e. will always do at least as well from here on.
+    pub fn dominance<C: Hash + Eq>(
+        mut self,
+        key: impl Fn(&K) -> C + 'a,
+        dominates: impl Fn((&K, V), (&K, V)) -> bool + 'a,
+    ) -> Self {
+        let key = Rc::new(key);
+        let hasher = RandomState::new();
+        let bucket_key = key.clone();
+        self.dominance = Some(Dominance {
+            bucket: Box::new(move |state| hasher.hash_one(bucket_key(state))),
+            dominates: Box::new(move |a, b| key(a.0) == key(b.0) && dominates(a, b)),
+        });
+        let states = self.states.drain().collect();
+        self.replace(states);
+        self
+    }
+
+    /// Keep at most `cap` states after each step, the ones with the best values.
+    pub fn cap(mut self, cap: usize) -> Self {
+        self.cap = Some(cap);
+        let states = self.states.drain().collect();
+        self.replace(states);
+        self
+    }
+
+    /// Steps taken so far.
+    pub fn time(&self) -> usize {
+        self.time
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    pub fn get(&self, state: &K) -> Option<V> {
+        self.states.get(state).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, V)> {
+        self.states.iter().map(|(k, v)| (k, *v))
+    }
+
+    /// The best value of any state.
+    pub fn best(&self) -> Option<V> {
+        self.states
+            .values()
+            .min_by(|a, b| self.merge.order(*a, *b))
+            .copied()
+    }
+
+    /// Move every state on one step, merging states reached more than once.
+    pub fn step(&mut self) {
+        let mut next = vec![];
+        for (state, value) in self.states.iter() {
+            (self.successors)(state, *value, &mut next);
+        }
+        self.replace(next);
+        self.time += 1;
+    }
+
+    /// Take `steps` steps, stopping early if every state dies.
+    pub fn run(&mut self, steps: usize) {
+        for _ in 0..steps {
+            if self.is_empty() {
+                break;
+            }
+            self.step();
+        }
+    }
+
+    fn replace(&mut self, states: Vec<(K, V)>) {
+        let merge = self.merge;
+        let mut merged: HashMap<K, V> = HashMap::with_capacity(states.len());
+        for (state, value) in states {
+            merged
+                .entry(state)
+                .and_modify(|cur| {
+                    if merge.order(&value, cur).is_lt() {
+                        *cur = value;
+                    }
+                })
+                .or_insert(value);
+        }
+        if self.dominance.is_none() && self.cap.is_none_or(|cap| merged.len() <= cap) {
+            self.states = merged;
+            return;
+        }
+        // Best first, so a cap keeps the best and equal states go in a fixed order.
+        let mut ranked = merged.into_iter().collect::<Vec<_>>();
+        ranked.sort_by(|a, b| merge.order(&a.1, &b.1).then_with(|| a.0.cmp(&b.0)));
+        if let Some(dominance) = self.dominance.as_ref() {
+            let mut buckets: HashMap<u64, Vec<usize>> = HashMap::new();
+            for (idx, (state, _)) in ranked.iter().enumerate() {
+                buckets
+                    .entry((dominance.bucket)(state))
+                    .or_default()
+                    .push(idx);
+            }
+            let dominates = |a: usize, b: usize| {
+                (dominance.dominates)((&ranked[a].0, ranked[a].1), (&ranked[b].0, ranked[b].1))
+            };
+            let mut keep = vec![true; ranked.len()];
+            for bucket in buckets.values() {
+                for b in bucket.iter() {
+                    // Of states that dominate each other only the first is kept.
+                    keep[*b] = !bucket
+                        .iter()
+                        .any(|a| a != b && dominates(*a, *b) && (a < b || !dominates(*b, *a)));
+                }
+            }
+            let mut keep = keep.into_iter();
+            ranked.retain(|_| keep.next().unwrap());
+        }
+        if let Some(cap) = self.cap {
+            ranked.truncate(cap);
+        }
+        self.states = ranked.into_iter().collect();
+    }
+}
+
+impl<K: Hash + Eq + Ord + Clone> Frontier<'_, K, i64> {
+    /// Step until the frontier looks the same as it did earlier, once `canonical` has
+    /// split every state into a state within one period of the map and how many periods
+    /// along it is. Values only have to match up to a constant. Gives up after `limit`
+    /// steps or when every state dies.
+    pub fn find_period(
+        &mut self,
+        canonical: impl Fn(&K) -> (K, i64),
+        limit: usize,
+    ) -> Option<Period> {
+        let mut seen = HashMap::new();
+        for _ in 0..=limit {
+            if self.is_empty() {
+                return None;
+            }
+            let (shape, offset, best) = self.shape(&canonical);
+            if let Some((time, prev_offset, prev_best)) = seen.get(&shape) {
+                return Some(Period {
+                    start: *time,
+                    steps: self.time - time,
+                    shift: offset - prev_offset,
+                    change: best - prev_best,
+                });
+            }
+            seen.insert(shape, (self.time, offset, best));
+            self.step();
+        }
+        None
+    }
+
+    /// Jump `count` repeats of `period` ahead without stepping. `translate` moves a
+    /// state along by a number of periods of the map.
+    pub fn skip_periods(
+        &mut self,
+        period: &Period,
+        count: usize,
+        translate: impl Fn(&K, i64) -> K,
+    ) {
+        let shift = period.shift * count as i64;
+        let change = period.change * count as i64;
+        self.states = self
+            .states
+            .drain()
+            .map(|(state, value)| (translate(&state, shift), value + change))
+            .collect();
+        self.time += period.steps * count;
+    }
+
+    /// Every state relative to the one furthest back and the best value, plus those two.
+    fn shape(&self, canonical: &impl Fn(&K) -> (K, i64)) -> (Shape<K>, i64, i64) {
+        let best = self.best().unwrap_or_default();
+        let canon = self
+            .states
+            .iter()
+            .map(|(state, value)| {
+                let (state, offset) = canonical(state);
+                (state, offset, *value)
+            })
+            .collect::<Vec<_>>();
+        let base = canon.iter().map(|c| c.1).min().unwrap_or_default();
+        let mut shape = canon
+            .into_iter()
+            .map(|(state, offset, value)| (state, offset - base, value - best))
+            .collect::<Vec<_>>();
+        shape.sort();
+        (shape, base, best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        // Walk right or left along a line, gaining the position as points.
+        let walk = |pos: &i64, value: i64| [(pos + 1, value + pos + 1), (pos - 1, value + pos - 1)];
+        let mut highest = Frontier::new([(0, 0)], Merge::Max, walk);
+        highest.run(2);
+        assert_eq!(vec![(-2, -3), (0, 1), (2, 3)], sorted(&highest));
+        let mut lowest = Frontier::new([(0, 0)], Merge::Min, walk);
+        lowest.run(2);
+        assert_eq!(Some(-1), lowest.get(&0));
+        assert_eq!(Some(-3), lowest.best());
+        assert_eq!(2, lowest.time());
+    }
+
+    #[test]
+    fn test_dominance_and_cap() {
+        // States are (lane, pos), and one further along with no fewer points dominates.
+        let walk =
+            |s: &(i64, i64), value: i64| [((s.0, s.1 + 1), value - 1), ((s.0, s.1 + 2), value - 1)];
+        let mut frontier = Frontier::new([((0, 0), 10), ((1, 0), 8)], Merge::Max, walk)
+            .dominance(|s| s.0, |a, b| a.0.1 >= b.0.1 && a.1 >= b.1);
+        frontier.step();
+        assert_eq!(vec![((0, 2), 9), ((1, 2), 7)], sorted(&frontier));
+        let mut capped = Frontier::new([((0, 0), 10), ((1, 0), 8)], Merge::Max, walk).cap(3);
+        capped.run(3);
+        assert_eq!(3, capped.len());
+        assert_eq!(vec![((0, 3), 7), ((0, 4), 7), ((0, 5), 7)], sorted(&capped));
+    }
+
+    #[test]
+    fn test_periodic() {
+        // Walk right on a repeating strip of width 3 where every third cell costs 2.
+        let walk = |pos: &i64, value: i64| {
+            let cost = if (pos + 1) % 3 == 0 { 2 } else { 0 };
+            [(pos + 1, value - cost)]
+        };
+        let mut frontier = Frontier::new([(0, 100)], Merge::Max, walk);
+        let period = frontier.find_period(|pos| (pos % 3, pos / 3), 10).unwrap();
+        assert_eq!(
+            Period {
+                start: 0,
+                steps: 3,
+                shift: 1,
+                change: -2
+            },
+            period
+        );
+        frontier.skip_periods(&period, 10, |pos, shift| pos + 3 * shift);
+        let mut slow = Frontier::new([(0, 100)], Merge::Max, walk);
+        slow.run(frontier.time());
+        assert_eq!(sorted(&slow), sorted(&frontier));
+    }
+
+    fn sorted<K: Hash + Eq + Ord + Clone, V: Cost>(frontier: &Frontier<K, V>) -> Vec<(K, V)> {
+        let mut states = frontier
+            .iter()
+            .map(|(k, v)| (k.clone(), v))
+            .collect::<Vec<_>>();
+        states.sort();
+        states
+    }
+}
@@ -0,0 +1,177 @@
+//! Best totals over walks along a line where each time step moves the offset by one
+//! of a fixed set of steps.
+
+use std::ops::RangeInclusive;
+
+use crate::search::Cost;
+
+/// Walks that start at offset 0 and take one of `steps` at each time `1..=times`,
+/// scoring the offset they land on.
+#[derive(Debug, Clone)]
+pub struct Lattice {
+    steps: Vec<i64>,
+    times: usize,
+}
+
+/// Best totals for every offset at one time, plus where each came from.
+struct Layer<S> {
+    best: Vec<Option<(S, S)>>,
+    from: Vec<(usize, usize)>,
+}
+
+impl Lattice {
+    /// Panics if `steps` is empty.
+    pub fn new(steps: &[i64], times: usize) -> Self {
+        assert!(!steps.is_empty(), "Lattice needs at least one step");
+        let mut steps = steps.to_vec();
+        steps.sort_unstable();
+        steps.dedup();
+        Self { steps, times }
+    }
+
+    /// Offsets that might be reached by `time`.
+    pub fn range(&self, time: usize) -> RangeInclusive<i64> {
+        let time = time as i64;
+        let lo = self.steps[0] * time;
+        let hi = self.steps[self.steps.len() - 1] * time;
+        lo.min(0)..=hi.max(0)
+    }
+
+    /// Lowest and highest total of `score(time, offset)` over every walk. `score` is
+    /// called once for each reachable time and offset.
+    pub fn min_max<S: Cost>(&self, score: impl FnMut(usize, i64) -> S) -> (S, S) {
+        let layers = self.run(score, false);
+        let last = &layers[layers.len() - 1].best;
+        let min = last.iter().flatten().map(|b| b.0).min().unwrap();
+        let max = last.iter().flatten().map(|b| b.1).max().unwrap();
+        (min, max)
+    }
+
+    /// Lowest total and the offsets at times `1..=times` of a walk that gets it.
+    pub fn argmin<S: Cost>(&self, score: impl FnMut(usize, i64) -> S) -> (S, Vec<i64>) {
+        self.best_path(score, false)
+    }
+
+    /// Highest total and the offsets at times `1..=times` of a walk that gets it.
+    pub fn argmax<S: Cost>(&self, score: impl FnMut(usize, i64) -> S) -> (S, Vec<i64>) {
+        self.best_path(score, true)
+    }
+
+    fn best_path<S: Cost>(
+        &self,
+        score: impl FnMut(usize, i64) -> S,
+        highest: bool,
+    ) -> (S, Vec<i64>) {
+        let layers = self.run(score, true);
+        let pick = |b: &(S, S)| if highest { b.1 } else { b.0 };
+        let last = &layers[layers.len() - 1].best;
+        let candidates = last
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, b)| b.as_ref().map(|b| (pick(b), idx)));
+        let (total, mut idx) = if highest {
+            candidates.max_by_key(|c| c.0).unwrap()
+        } else {
+            candidates.min_by_key(|c| c.0).unwrap()
+        };
+        let base = *self.range(self.times).start();
+        let mut path = vec![0; self.times];
+        for time in (1..=self.times).rev() {
+            path[time - 1] = base + idx as i64;
+            let from = layers[time].from[idx];
+            idx = if highest { from.1 } else { from.0 };
+        }
+        (total, path)
+    }
+
+    /// One layer per time, indexed by `offset - range(times).start()`.
+    fn run<S: Cost>(&self, mut score: impl FnMut(usize, i64) -> S, record: bool) -> Vec<Layer<S>> {
+        let full = self.range(self.times);
+        let base = *full.start();
+        let width = (full.end() - base) as usize + 1;
+        let mut first = vec![None; width];
+        first[(-base) as usize] = Some((S::default(), S::default()));
+        let mut layers = vec![Layer {
+            best: first,
+            from: vec![],
+        }];
+        for time in 1..=self.times {
+            let prev = &layers[layers.len() - 1].best;
+            let mut best = vec![None; width];
+            let mut from = if record { vec![(0, 0); width] } else { vec![] };
+            for offset in self.range(time) {
+                let idx = (offset - base) as usize;
+                // Best (lowest, highest) totals that can step onto this offset.
+                let mut reach: Option<((S, usize), (S, usize))> = None;
+                for step in self.steps.iter() {
+                    let Some(src) = usize::try_from(offset - step - base)
+                        .ok()
+                        .filter(|src| *src < width)
+                    else {
+                        continue;
+                    };
+                    let Some((lo, hi)) = prev[src] else {
+                        continue;
+                    };
+                    reach = Some(match reach {
+                        None => ((lo, src), (hi, src)),
+                        Some((min, max)) => (
+                            if lo < min.0 { (lo, src) } else { min },
+                            if hi > max.0 { (hi, src) } else { max },
+                        ),
+                    });
+                }
+                let Some((min, max)) = reach else {
+                    continue;
+                };
+                let value = score(time, offset);
+                best[idx] = Some((min.0 + value, max.0 + value));
+                if record {
+                    from[idx] = (min.1, max.1);
+                }
+            }
+            if !record {
+                // Only the latest layer is needed for totals.
+                layers.clear();
+            }
+            layers.push(Layer { best, from });
+        }
+        layers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_max() {
+        // Score the distance from 2, so the best walk heads there and stays.
+        let lattice = Lattice::new(&[-1, 0, 1], 4);
+        let (min, max) = lattice.min_max(|_, offset| offset.abs_diff(2));
+        assert_eq!(1, min);
+        assert_eq!(3 + 4 + 5 + 6, max);
+        assert_eq!(-4..=4, lattice.range(4));
+    }
+
+    #[test]
+    fn test_paths() {
+        let lattice = Lattice::new(&[1, -1, 0, 1], 4);
+        let (min, path) = lattice.argmin(|_, offset| offset.abs_diff(2));
+        assert_eq!((1, vec![1, 2, 2, 2]), (min, path));
+        let (max, path) = lattice.argmax(|time, offset| (time as i64 * offset).unsigned_abs());
+        assert_eq!((1 + 4 + 9 + 16, vec![1, 2, 3, 4]), (max, path));
+    }
+
+    #[test]
+    fn test_parity() {
+        // Only even offsets are reachable after an even number of ±1 steps.
+        let lattice = Lattice::new(&[-1, 1], 2);
+        let mut seen = vec![];
+        lattice.min_max(|time, offset| {
+            seen.push((time, offset));
+            0_u64
+        });
+        assert_eq!(vec![(1, -1), (1, 1), (2, -2), (2, 0), (2, 2)], seen);
+    }
+}
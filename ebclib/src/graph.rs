@@ -0,0 +1,3 @@
+//! Graph algorithms that don't belong to a single quest.
+
+pub mod mst;
@@ -0,0 +1,274 @@
+//! Minimum spanning trees and the clusters left after cutting long edges.
+
+use std::ops::Add;
+
+/// Weights that can be compared and summed. Floats are fine as long as they aren't NaN.
+pub trait Weight: Copy + PartialOrd + Add<Output = Self> + Default {}
+
+impl<T: Copy + PartialOrd + Add<Output = T> + Default> Weight for T {}
+
+/// Distance between two points.
+pub trait Metric<P> {
+    type Weight: Weight;
+
+    fn distance(&self, a: &P, b: &P) -> Self::Weight;
+}
+
+/// Sum of the differences along each axis.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Manhattan;
+
+/// Straight line distance.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Euclidean;
+
+/// Largest difference along any one axis.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Chebyshev;
+
+impl<const D: usize> Metric<[i64; D]> for Manhattan {
+    type Weight = u64;
+
+    fn distance(&self, a: &[i64; D], b: &[i64; D]) -> Self::Weight {
+        a.iter().zip(b).map(|(a, b)| a.abs_diff(*b)).sum()
+    }
+}
+
+impl<const D: usize> Metric<[i64; D]> for Euclidean {
+    type Weight = f64;
+
+    fn distance(&self, a: &[i64; D], b: &[i64; D]) -> Self::Weight {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| (a.abs_diff(*b) as f64).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+impl<const D: usize> Metric<[i64; D]> for Chebyshev {
+    type Weight = u64;
+
+    fn distance(&self, a: &[i64; D], b: &[i64; D]) -> Self::Weight {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Disjoint sets over `0..len` with path halving and union by size.
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    pub fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+        }
+    }
+
+    /// Representative of the set holding `node`.
+    pub fn find(&mut self, mut node: usize) -> usize {
+        while self.parent[node] != node {
+            self.parent[node] = self.parent[self.parent[node]];
+            node = self.parent[node];
+        }
+        node
+    }
+
+    /// Merge the sets holding `a` and `b`. False if they were already together.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            (a, b) = (b, a);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        true
+    }
+
+    /// Number of nodes in the set holding `node`.
+    pub fn size(&mut self, node: usize) -> usize {
+        let root = self.find(node);
+        self.size[root]
+    }
+}
+
+/// The edges of a minimum spanning forest over nodes `0..nodes`.
+#[derive(Debug, Clone)]
+pub struct SpanningForest<W> {
+    nodes: usize,
+    edges: Vec<(usize, usize, W)>,
+}
+
+/// A connected group of nodes and the weight of the tree joining them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cluster<W> {
+    pub nodes: Vec<usize>,
+    pub weight: W,
+}
+
+impl<W: Weight> SpanningForest<W> {
+    /// `(from, to, weight)` for every edge in the forest.
+    pub fn edges(&self) -> &[(usize, usize, W)] {
+        &self.edges
+    }
+
+    /// Total weight of every edge.
+    pub fn weight(&self) -> W {
+        self.edges.iter().fold(W::default(), |acc, e| acc + e.2)
+    }
+
+    /// Groups left after dropping every edge longer than `max_edge`. These are the
+    /// same groups as joining every pair of nodes within `max_edge` of each other, and
+    /// each weight is that group's own spanning tree. Largest groups first.
+    pub fn clusters(&self, max_edge: W) -> Vec<Cluster<W>> {
+        let mut sets = UnionFind::new(self.nodes);
+        for (from, to, weight) in self.edges.iter() {
+            if *weight <= max_edge {
+                sets.union(*from, *to);
+            }
+        }
+        let mut index = vec![usize::MAX; self.nodes];
+        let mut clusters: Vec<Cluster<W>> = vec![];
+        for node in 0..self.nodes {
+            let root = sets.find(node);
+            if index[root] == usize::MAX {
+                index[root] = clusters.len();
+                clusters.push(Cluster {
+                    nodes: vec![],
+                    weight: W::default(),
+                });
+            }
+            clusters[index[root]].nodes.push(node);
+        }
+        for (from, _, weight) in self.edges.iter() {
+            if *weight <= max_edge {
+                let cluster = &mut clusters[index[sets.find(*from)]];
+                cluster.weight = cluster.weight + *weight;
+            }
+        }
+        clusters.sort_by_key(|c| std::cmp::Reverse(c.nodes.len()));
+        clusters
+    }
+}
+
+/// Kruskal's algorithm over an edge list. Disconnected graphs give a forest.
+pub fn kruskal<W: Weight>(nodes: usize, edges: &[(usize, usize, W)]) -> SpanningForest<W> {
+    let mut sorted = edges.to_vec();
+    sorted.sort_by(|a, b| a.2.partial_cmp(&b.2).expect("Weights must be comparable"));
+    let mut sets = UnionFind::new(nodes);
+    let edges = sorted
+        .into_iter()
+        .filter(|(from, to, _)| sets.union(*from, *to))
+        .collect();
+    SpanningForest { nodes, edges }
+}
+
+/// Prim's algorithm over the complete graph between `points`. Runs in O(V²) without
+/// building the edge list, which suits dense point clouds.
+pub fn prim<P, M: Metric<P>>(points: &[P], metric: &M) -> SpanningForest<M::Weight> {
+    let mut edges = Vec::with_capacity(points.len().saturating_sub(1));
+    let mut in_tree = vec![false; points.len()];
+    // Cheapest known link from each point outside the tree into it.
+    let mut best: Vec<Option<(M::Weight, usize)>> = vec![None; points.len()];
+    let mut next = (!points.is_empty()).then_some(0);
+    while let Some(node) = next {
+        in_tree[node] = true;
+        if let Some((weight, from)) = best[node] {
+            edges.push((from, node, weight));
+        }
+        next = None;
+        for other in 0..points.len() {
+            if in_tree[other] {
+                continue;
+            }
+            let dist = metric.distance(&points[node], &points[other]);
+            if best[other].is_none_or(|(cur, _)| dist < cur) {
+                best[other] = Some((dist, node));
+            }
+            if next.is_none_or(|n: usize| best[other].unwrap().0 < best[n].unwrap().0) {
+                next = Some(other);
+            }
+        }
+    }
+    SpanningForest {
+        nodes: points.len(),
+        edges,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stars() -> Vec<[i64; 2]> {
+        vec![[1, 5], [5, 5], [3, 4], [1, 1], [3, 1]]
+    }
+
+    #[test]
+    fn test_prim() {
+        let tree = prim(&stars(), &Manhattan);
+        assert_eq!(4, tree.edges().len());
+        assert_eq!(11, tree.weight());
+    }
+
+    #[test]
+    fn test_kruskal_matches_prim() {
+        let points = stars();
+        let mut edges = vec![];
+        for a in 0..points.len() {
+            for b in a + 1..points.len() {
+                edges.push((a, b, Manhattan.distance(&points[a], &points[b])));
+            }
+        }
+        assert_eq!(
+            prim(&points, &Manhattan).weight(),
+            kruskal(5, &edges).weight()
+        );
+    }
+
+    #[test]
+    fn test_metrics() {
+        assert_eq!(7, Manhattan.distance(&[0, 0], &[3, -4]));
+        assert_eq!(5.0, Euclidean.distance(&[0, 0], &[3, -4]));
+        assert_eq!(4, Chebyshev.distance(&[0, 0, 0], &[3, -4, 1]));
+    }
+
+    #[test]
+    fn test_clusters() {
+        let points = vec![[0, 0], [0, 2], [10, 10], [10, 11], [10, 13], [50, 50]];
+        let clusters = prim(&points, &Manhattan).clusters(3);
+        let shapes = clusters
+            .iter()
+            .map(|c| (c.nodes.len(), c.weight))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![(3, 3), (2, 2), (1, 0)], shapes);
+    }
+
+    #[test]
+    fn test_forest() {
+        let forest = kruskal(4, &[(0, 1, 2), (2, 3, 1), (0, 1, 1)]);
+        assert_eq!(2, forest.edges().len());
+        assert_eq!(2, forest.clusters(10).len());
+    }
+
+    #[test]
+    fn test_union_find() {
+        let mut sets = UnionFind::new(4);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 1));
+        assert!(!sets.union(0, 2));
+        assert_eq!(3, sets.size(2));
+        assert_eq!(1, sets.size(3));
+    }
+}
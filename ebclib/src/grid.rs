@@ -0,0 +1,168 @@
+//! Dense rectangular grids addressed by `(row, col)`.
+
+pub mod weighted;
+
+use std::ops::{Index, IndexMut};
+
+/// A `rows` by `cols` grid stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    rows: usize,
+    cols: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(rows: usize, cols: usize, fill: T) -> Self {
+        Self {
+            rows,
+            cols,
+            cells: vec![fill; rows * cols],
+        }
+    }
+
+    /// Set every cell to `value`.
+    pub fn fill(&mut self, value: T) {
+        self.cells.fill(value);
+    }
+}
+
+impl<T> Grid<T> {
+    /// Build from rows of cells. Panics if the rows are different lengths.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let cols = rows.first().map_or(0, |r| r.len());
+        assert!(
+            rows.iter().all(|r| r.len() == cols),
+            "Rows are different lengths"
+        );
+        Self {
+            rows: rows.len(),
+            cols,
+            cells: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, (row, col): (usize, usize)) -> Option<&T> {
+        (row < self.rows && col < self.cols).then(|| &self.cells[row * self.cols + col])
+    }
+
+    pub fn get_mut(&mut self, (row, col): (usize, usize)) -> Option<&mut T> {
+        (row < self.rows && col < self.cols).then(|| &mut self.cells[row * self.cols + col])
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(idx, v)| ((idx / self.cols, idx % self.cols), v))
+    }
+
+    /// Positions of the cells for which `wanted` is true.
+    pub fn positions(&self, wanted: impl Fn(&T) -> bool) -> Vec<(usize, usize)> {
+        self.iter()
+            .filter_map(|(pos, v)| wanted(v).then_some(pos))
+            .collect()
+    }
+
+    /// True for cells in the outer ring.
+    pub fn on_edge(&self, (row, col): (usize, usize)) -> bool {
+        row == 0 || col == 0 || row + 1 == self.rows || col + 1 == self.cols
+    }
+
+    /// The up to four in bounds neighbours of a cell.
+    pub fn cardinals(&self, (row, col): (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        let (rows, cols) = (self.rows, self.cols);
+        [(-1, 0), (0, 1), (1, 0), (0, -1)]
+            .into_iter()
+            .filter_map(move |(dr, dc)| {
+                let row = row.checked_add_signed(dr).filter(|r| *r < rows)?;
+                let col = col.checked_add_signed(dc).filter(|c| *c < cols)?;
+                Some((row, col))
+            })
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            rows: self.rows,
+            cols: self.cols,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl Grid<char> {
+    /// One row per line.
+    pub fn parse<S: AsRef<str>>(lines: &[S]) -> Self {
+        Self::from_rows(lines.iter().map(|l| l.as_ref().chars().collect()).collect())
+    }
+
+    /// One row per line, with short lines filled out to the longest using `fill`.
+    pub fn parse_padded<S: AsRef<str>>(lines: &[S], fill: char) -> Self {
+        let cols = lines
+            .iter()
+            .map(|l| l.as_ref().chars().count())
+            .max()
+            .unwrap_or(0);
+        Self::from_rows(
+            lines
+                .iter()
+                .map(|l| {
+                    let mut row = l.as_ref().chars().collect::<Vec<_>>();
+                    row.resize(cols, fill);
+                    row
+                })
+                .collect(),
+        )
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &Self::Output {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos:?} is outside the grid"))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, pos: (usize, usize)) -> &mut Self::Output {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos:?} is outside the grid"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::parse(&["#.#", "..P"]);
+        assert_eq!((2, 3), (grid.rows(), grid.cols()));
+        assert_eq!('P', grid[(1, 2)]);
+        assert_eq!(None, grid.get((2, 0)));
+        assert_eq!(vec![(1, 2)], grid.positions(|c| *c == 'P'));
+        let padded = Grid::parse_padded(&["#", "..P"], ' ');
+        assert_eq!(' ', padded[(0, 2)]);
+    }
+
+    #[test]
+    fn test_cardinals() {
+        let grid = Grid::new(3, 3, 0);
+        assert_eq!(2, grid.cardinals((0, 0)).count());
+        assert_eq!(4, grid.cardinals((1, 1)).count());
+        assert_eq!(3, grid.cardinals((2, 1)).count());
+        assert!(grid.on_edge((2, 1)));
+        assert!(!grid.on_edge((1, 1)));
+    }
+}
@@ -0,0 +1,166 @@
+//! Grids as weighted graphs between four way neighbours.
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use super::Grid;
+
+type Passable<'a, T> = Box<dyn Fn(&T) -> bool + 'a>;
+type StepCost<'a, T> = Box<dyn Fn(&T, &T) -> u64 + 'a>;
+
+/// Ready made step costs, given the cell being left and the cell being entered.
+pub mod cost {
+    /// Every move costs `step`.
+    pub fn uniform<T>(step: u64) -> impl Fn(&T, &T) -> u64 {
+        move |_, _| step
+    }
+
+    /// `step` plus the difference in height between the two cells.
+    pub fn height_difference<T>(step: u64, height: impl Fn(&T) -> i64) -> impl Fn(&T, &T) -> u64 {
+        move |from, to| step + height(from).abs_diff(height(to))
+    }
+
+    /// `step` plus the distance between two levels on a dial of `levels` that wraps
+    /// round. On a 0 to 9 dial the dial distance from 9 to 1 is 2, so the move costs
+    /// `step + 2`.
+    pub fn cyclic_level<T>(
+        step: u64,
+        levels: i64,
+        level: impl Fn(&T) -> i64,
+    ) -> impl Fn(&T, &T) -> u64 {
+        move |from, to| {
+            let diff = (level(from) - level(to)).rem_euclid(levels);
+            step + diff.min(levels - diff) as u64
+        }
+    }
+}
+
+/// A grid where moves between four way neighbours have a cost. Every cell is passable
+/// and every move costs 1 until set otherwise.
+pub struct GridGraph<'a, T> {
+    grid: &'a Grid<T>,
+    passable: Passable<'a, T>,
+    cost: StepCost<'a, T>,
+}
+
+impl<'a, T> GridGraph<'a, T> {
+    pub fn new(grid: &'a Grid<T>) -> Self {
+        Self {
+            grid,
+            passable: Box::new(|_| true),
+            cost: Box::new(cost::uniform(1)),
+        }
+    }
+
+    /// Only cells where `passable` is true can be entered or left.
+    pub fn passable(mut self, passable: impl Fn(&T) -> bool + 'a) -> Self {
+        self.passable = Box::new(passable);
+        self
+    }
+
+    /// Cost of moving from the first cell to the second. See [`cost`] for common ones.
+    pub fn cost(mut self, cost: impl Fn(&T, &T) -> u64 + 'a) -> Self {
+        self.cost = Box::new(cost);
+        self
+    }
+
+    pub fn grid(&self) -> &Grid<T> {
+        self.grid
+    }
+
+    /// Passable neighbours of `pos` and the cost of moving to each.
+    pub fn edges(&self, pos: (usize, usize)) -> impl Iterator<Item = ((usize, usize), u64)> + '_ {
+        let from = &self.grid[pos];
+        self.grid
+            .cardinals(pos)
+            .filter(|next| (self.passable)(&self.grid[*next]))
+            .map(move |next| (next, (self.cost)(from, &self.grid[next])))
+    }
+
+    /// Cheapest cost from the nearest source to every cell.
+    pub fn distances(
+        &self,
+        sources: impl IntoIterator<Item = (usize, usize)>,
+    ) -> Grid<Option<u64>> {
+        let mut dist = Grid::new(self.grid.rows(), self.grid.cols(), None);
+        self.dijkstra(&mut dist, sources, |_| false);
+        dist
+    }
+
+    /// The target that is cheapest to reach from any of the sources, and its cost.
+    pub fn nearest(
+        &self,
+        sources: impl IntoIterator<Item = (usize, usize)>,
+        targets: impl IntoIterator<Item = (usize, usize)>,
+    ) -> Option<((usize, usize), u64)> {
+        let mut is_target = Grid::new(self.grid.rows(), self.grid.cols(), false);
+        targets.into_iter().for_each(|pos| is_target[pos] = true);
+        let mut dist = Grid::new(self.grid.rows(), self.grid.cols(), None);
+        self.dijkstra(&mut dist, sources, |pos| is_target[pos])
+    }
+
+    /// Dijkstra from every source at once into `dist`, stopping at the first cell where
+    /// `stop` is true and returning it with its cost.
+    fn dijkstra(
+        &self,
+        dist: &mut Grid<Option<u64>>,
+        sources: impl IntoIterator<Item = (usize, usize)>,
+        stop: impl Fn((usize, usize)) -> bool,
+    ) -> Option<((usize, usize), u64)> {
+        let mut queue = BinaryHeap::new();
+        for source in sources {
+            if (self.passable)(&self.grid[source]) {
+                dist[source] = Some(0);
+                queue.push(Reverse((0, source)));
+            }
+        }
+        while let Some(Reverse((cost, pos))) = queue.pop() {
+            if dist[pos].is_some_and(|best| best < cost) {
+                continue;
+            }
+            if stop(pos) {
+                return Some((pos, cost));
+            }
+            for (next, step) in self.edges(pos) {
+                let cost = cost + step;
+                if dist[next].is_none_or(|best| cost < best) {
+                    dist[next] = Some(cost);
+                    queue.push(Reverse((cost, next)));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cost_models() {
+        let dial = cost::cyclic_level(1, 10, |v: &i64| *v);
+        assert_eq!(3, dial(&9, &1));
+        assert_eq!(6, dial(&2, &7));
+        assert_eq!(1, dial(&4, &4));
+        let hills = cost::height_difference(0, |v: &i64| *v);
+        assert_eq!(5, hills(&-2, &3));
+        assert_eq!(7, cost::uniform(7)(&'a', &'b'));
+    }
+
+    #[test]
+    fn test_nearest() {
+        let grid = Grid::parse(&["S5.1E", "S#.#.", "S0001"]);
+        let level = |c: &char| c.to_digit(10).map_or(0, |d| d as i64);
+        let graph = GridGraph::new(&grid)
+            .passable(|c| *c != '#')
+            .cost(cost::cyclic_level(1, 10, level));
+        let starts = grid.positions(|c| *c == 'S');
+        let end = grid.positions(|c| *c == 'E');
+        assert_eq!(Some(((0, 4), 8)), graph.nearest(starts.clone(), end));
+
+        let distances = graph.distances(starts);
+        assert_eq!(Some(0), distances[(1, 0)]);
+        assert_eq!(None, distances[(1, 1)]);
+        assert_eq!(None, graph.nearest([(0, 0)], [(1, 1)]));
+    }
+}
@@ -0,0 +1,80 @@
+//! Map repeated names onto small dense ids.
+
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+/// Hands out ids `0, 1, 2, ...` in first seen order so names can index into vectors.
+#[derive(Debug, Clone)]
+pub struct Interner<T> {
+    values: Vec<T>,
+    index: HashMap<T, usize>,
+}
+
+impl<T> Default for Interner<T> {
+    fn default() -> Self {
+        Self {
+            values: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Hash + Eq + Clone> Interner<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the id for `value`, adding it if it hasn't been seen before.
+    pub fn intern(&mut self, value: T) -> usize {
+        if let Some(id) = self.index.get(&value) {
+            return *id;
+        }
+        let id = self.values.len();
+        self.index.insert(value.clone(), id);
+        self.values.push(value);
+        id
+    }
+
+    /// Get the id for `value` without adding it.
+    pub fn get<Q>(&self, value: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.get(value).copied()
+    }
+
+    /// The value behind an id.
+    pub fn resolve(&self, id: usize) -> &T {
+        &self.values[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// All values in id order.
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let mut names = Interner::new();
+        assert_eq!(0, names.intern("A".to_string()));
+        assert_eq!(1, names.intern("B".to_string()));
+        assert_eq!(0, names.intern("A".to_string()));
+        assert_eq!(Some(1), names.get("B"));
+        assert_eq!(None, names.get("C"));
+        assert_eq!("B", names.resolve(1));
+        assert_eq!(2, names.len());
+    }
+}
@@ -1,3 +1,28 @@
+pub mod ballistics;
+pub mod bitset;
+pub mod combinatorics;
+pub mod cycle;
+pub mod dp;
+pub mod graph;
+pub mod grid;
+pub mod intern;
+pub mod linalg;
+pub mod modint;
+pub mod par;
+pub mod parse;
+pub mod registry;
+pub mod render;
+pub mod ring;
+pub mod schema;
+pub mod search;
+pub mod step;
+pub mod symbol;
+pub mod track;
+pub mod tree;
+pub mod voxel;
+
+pub use ebclib_derive::quest;
+
 use std::ops::Deref;
 
 /// Get the midpoint(s) of an array. If the array is sorted this will get the median value(s);
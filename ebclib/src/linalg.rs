@@ -0,0 +1,290 @@
+//! Square integer matrices for linear recurrences such as population growth.
+
+use std::{
+    fmt::Display,
+    ops::{Add, Index, IndexMut, Mul},
+};
+
+use num_bigint::BigUint;
+
+use crate::{
+    intern::Interner,
+    parse::{self, ParseError},
+};
+
+/// Values that can be stored in a [`Matrix`].
+pub trait Scalar: Clone + Add<Output = Self> + Mul<Output = Self> + From<u64> {
+    fn zero() -> Self {
+        Self::from(0)
+    }
+
+    fn one() -> Self {
+        Self::from(1)
+    }
+}
+
+impl Scalar for u64 {}
+impl Scalar for u128 {}
+impl Scalar for BigUint {}
+
+/// A square matrix stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matrix<T> {
+    size: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Scalar> Matrix<T> {
+    pub fn zeros(size: usize) -> Self {
+        Self {
+            size,
+            cells: vec![T::zero(); size * size],
+        }
+    }
+
+    pub fn identity(size: usize) -> Self {
+        let mut matrix = Self::zeros(size);
+        for idx in 0..size {
+            matrix[(idx, idx)] = T::one();
+        }
+        matrix
+    }
+
+    /// Number of rows (and columns).
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        self.mul_with(other, |v| v)
+    }
+
+    /// Raise the matrix to `exp` by repeated squaring.
+    pub fn pow(&self, exp: u64) -> Self {
+        self.pow_with(exp, |v| v)
+    }
+
+    /// Matrix times a column vector.
+    pub fn mul_vec(&self, vector: &[T]) -> Vec<T> {
+        assert_eq!(self.size, vector.len(), "Vector doesn't match matrix size");
+        (0..self.size)
+            .map(|row| {
+                (0..self.size).fold(T::zero(), |acc, col| {
+                    acc + self[(row, col)].clone() * vector[col].clone()
+                })
+            })
+            .collect()
+    }
+
+    /// A row vector times the matrix.
+    pub fn vec_mul(&self, vector: &[T]) -> Vec<T> {
+        assert_eq!(self.size, vector.len(), "Vector doesn't match matrix size");
+        (0..self.size)
+            .map(|col| {
+                (0..self.size).fold(T::zero(), |acc, row| {
+                    acc + vector[row].clone() * self[(row, col)].clone()
+                })
+            })
+            .collect()
+    }
+
+    /// Multiply, passing every product and running sum through `reduce`.
+    fn mul_with(&self, other: &Self, reduce: impl Fn(T) -> T) -> Self {
+        assert_eq!(self.size, other.size, "Matrix sizes don't match");
+        let mut res = Self::zeros(self.size);
+        for row in 0..self.size {
+            for mid in 0..self.size {
+                let left = &self[(row, mid)];
+                for col in 0..self.size {
+                    let product = reduce(left.clone() * other[(mid, col)].clone());
+                    res[(row, col)] = reduce(res[(row, col)].clone() + product);
+                }
+            }
+        }
+        res
+    }
+
+    fn pow_with(&self, mut exp: u64, reduce: impl Fn(T) -> T) -> Self {
+        let mut base = self.clone();
+        let mut res = Self::identity(self.size);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                res = res.mul_with(&base, &reduce);
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.mul_with(&base, &reduce);
+            }
+        }
+        res
+    }
+}
+
+impl Matrix<u128> {
+    /// Raise the matrix to `exp` keeping every entry below `modulus`.
+    pub fn pow_mod(&self, exp: u64, modulus: u64) -> Self {
+        let modulus = modulus as u128;
+        let reduced = Self {
+            size: self.size,
+            cells: self.cells.iter().map(|v| v % modulus).collect(),
+        };
+        reduced.pow_with(exp, |v| v % modulus)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        &self.cells[row * self.size + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Self::Output {
+        &mut self.cells[row * self.size + col]
+    }
+}
+
+impl<T: Display> Display for Matrix<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.cells.chunks(self.size.max(1)) {
+            let row = row.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Rewrite rules like `A:B,C` where each `A` becomes one `B` and one `C` every step.
+#[derive(Debug, Clone, Default)]
+pub struct Rules {
+    symbols: Interner<String>,
+    /// `(from, to)` for every produced symbol, repeats included.
+    produces: Vec<(usize, usize)>,
+    has_rule: Vec<bool>,
+}
+
+impl Rules {
+    /// Parse one rule per line. Blank lines are skipped.
+    pub fn parse<S: AsRef<str>>(lines: &[S]) -> Result<Self, ParseError> {
+        let mut rules = Self::default();
+        for line in parse::lines(lines) {
+            let (from, to) = line.keyed()?;
+            let from = rules.symbol_id(from.as_str());
+            rules.has_rule[from] = true;
+            for to in to {
+                let to = rules.symbol_id(to.as_str());
+                rules.produces.push((from, to));
+            }
+        }
+        Ok(rules)
+    }
+
+    fn symbol_id(&mut self, name: &str) -> usize {
+        let id = self.symbols.intern(name.to_string());
+        if id == self.has_rule.len() {
+            self.has_rule.push(false);
+        }
+        id
+    }
+
+    /// Id of a symbol.
+    pub fn symbol(&self, name: &str) -> Option<usize> {
+        self.symbols.get(name)
+    }
+
+    /// Every symbol, indexed by id.
+    pub fn symbols(&self) -> &[String] {
+        self.symbols.values()
+    }
+
+    /// Ids of the symbols that appear on the left of a rule.
+    pub fn sources(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.has_rule.len()).filter(|id| self.has_rule[*id])
+    }
+
+    /// Transition matrix where entry `(to, from)` counts how many `to` one `from` becomes.
+    pub fn matrix<T: Scalar>(&self) -> Matrix<T> {
+        let mut counts = vec![0_u64; self.symbols.len() * self.symbols.len()];
+        for (from, to) in self.produces.iter() {
+            counts[to * self.symbols.len() + from] += 1;
+        }
+        Matrix {
+            size: self.symbols.len(),
+            cells: counts.into_iter().map(T::from).collect(),
+        }
+    }
+
+    /// Counts of every symbol after `steps` starting from the counts in `start`.
+    pub fn evolve<T: Scalar>(&self, start: &[T], steps: u64) -> Vec<T> {
+        self.matrix().pow(steps).mul_vec(start)
+    }
+
+    /// Total population after `steps` for a single starting symbol of each kind,
+    /// indexed by the starting symbol's id.
+    pub fn totals<T: Scalar>(&self, steps: u64) -> Vec<T> {
+        let ones = vec![T::one(); self.symbols.len()];
+        self.matrix().pow(steps).vec_mul(&ones)
+    }
+
+    /// [`Rules::totals`] with every count taken modulo `modulus`.
+    pub fn totals_mod(&self, steps: u64, modulus: u64) -> Vec<u128> {
+        let ones = vec![1; self.symbols.len()];
+        self.matrix::<u128>()
+            .pow_mod(steps, modulus)
+            .vec_mul(&ones)
+            .into_iter()
+            .map(|v| v % modulus as u128)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> Rules {
+        Rules::parse(&["A:B,C", "B:C,A", "C:A"]).unwrap()
+    }
+
+    #[test]
+    fn test_identity_pow() {
+        let matrix = rules().matrix::<u64>();
+        assert_eq!(Matrix::identity(3), matrix.pow(0));
+        assert_eq!(matrix, matrix.pow(1));
+        assert_eq!(matrix.mul(&matrix).mul(&matrix), matrix.pow(3));
+    }
+
+    #[test]
+    fn test_evolve() {
+        let rules = rules();
+        let mut start = vec![0_u64; 3];
+        start[rules.symbol("A").unwrap()] = 1;
+        assert_eq!(8, rules.evolve(&start, 4).iter().sum::<u64>());
+    }
+
+    #[test]
+    fn test_totals() {
+        let rules = Rules::parse(&["A:B,C", "B:C,A,A", "C:A"]).unwrap();
+        let totals = rules.sources().map(|id| rules.totals::<u64>(20)[id]);
+        let (min, max) = totals.fold((u64::MAX, 0), |(lo, hi), v| (lo.min(v), hi.max(v)));
+        assert_eq!(268815, max - min);
+    }
+
+    #[test]
+    fn test_big_and_modular() {
+        let rules = rules();
+        let a = rules.symbol("A").unwrap();
+        let big = rules.totals::<BigUint>(200)[a].clone();
+        let modular = rules.totals_mod(200, 1_000_000_007)[a];
+        assert_eq!(big % 1_000_000_007_u64, BigUint::from(modular));
+    }
+
+    #[test]
+    fn test_sources() {
+        let rules = Rules::parse(&["A:B", "B:C"]).unwrap();
+        assert_eq!(vec!["A", "B", "C"], rules.symbols());
+        assert_eq!(vec![0, 1], rules.sources().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,257 @@
+//! Integers modulo a number only known at runtime.
+
+use std::{
+    fmt::Display,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+/// Anything that can be turned into a value in `0..modulus`.
+pub trait Residue {
+    fn residue(self, modulus: u64) -> u64;
+}
+
+macro_rules! residue_unsigned {
+    ($($t:ty),*) => {$(
+        impl Residue for $t {
+            fn residue(self, modulus: u64) -> u64 {
+                (self as u128 % modulus as u128) as u64
+            }
+        }
+    )*};
+}
+
+macro_rules! residue_signed {
+    ($($t:ty),*) => {$(
+        impl Residue for $t {
+            fn residue(self, modulus: u64) -> u64 {
+                (self as i128).rem_euclid(modulus as i128) as u64
+            }
+        }
+    )*};
+}
+
+residue_unsigned!(u8, u16, u32, u64, u128, usize);
+residue_signed!(i8, i16, i32, i64, i128, isize);
+
+/// A value in `0..modulus`. Every operation reduces straight away, widening to
+/// `u128` first so products of two values never overflow.
+///
+/// Plain integers can be used on either side of an operator and are reduced with the
+/// same modulus. Mixing two `ModInt`s with different moduli panics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ModInt {
+    value: u64,
+    modulus: u64,
+}
+
+impl ModInt {
+    pub fn new<T: Residue>(value: T, modulus: u64) -> Self {
+        assert!(modulus > 0, "Modulus must be positive");
+        Self {
+            value: value.residue(modulus),
+            modulus,
+        }
+    }
+
+    pub fn value(self) -> u64 {
+        self.value
+    }
+
+    pub fn modulus(self) -> u64 {
+        self.modulus
+    }
+
+    pub fn pow(self, mut exp: u64) -> Self {
+        let mut base = self;
+        let mut res = Self::new(1, self.modulus);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                res *= base;
+            }
+            base *= base;
+            exp >>= 1;
+        }
+        res
+    }
+
+    /// The multiplicative inverse, if the value and modulus are coprime.
+    pub fn inverse(self) -> Option<Self> {
+        // Extended Euclid keeping only the coefficient for the value.
+        let (mut old_r, mut r) = (self.value as i128, self.modulus as i128);
+        let (mut old_s, mut s) = (1_i128, 0_i128);
+        while r != 0 {
+            let quotient = old_r / r;
+            (old_r, r) = (r, old_r - quotient * r);
+            (old_s, s) = (s, old_s - quotient * s);
+        }
+        (old_r == 1 || self.modulus == 1).then(|| Self::new(old_s, self.modulus))
+    }
+}
+
+impl Residue for ModInt {
+    fn residue(self, modulus: u64) -> u64 {
+        assert_eq!(self.modulus, modulus, "Mixing different moduli");
+        self.value
+    }
+}
+
+impl Display for ModInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl Neg for ModInt {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(self.modulus - self.value, self.modulus)
+    }
+}
+
+impl<T: Residue> Add<T> for ModInt {
+    type Output = Self;
+
+    fn add(self, rhs: T) -> Self::Output {
+        let rhs = rhs.residue(self.modulus);
+        Self::new(self.value as u128 + rhs as u128, self.modulus)
+    }
+}
+
+impl<T: Residue> Sub<T> for ModInt {
+    type Output = Self;
+
+    fn sub(self, rhs: T) -> Self::Output {
+        let rhs = rhs.residue(self.modulus);
+        Self::new(
+            self.value as u128 + (self.modulus - rhs) as u128,
+            self.modulus,
+        )
+    }
+}
+
+impl<T: Residue> Mul<T> for ModInt {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        let rhs = rhs.residue(self.modulus);
+        Self::new(self.value as u128 * rhs as u128, self.modulus)
+    }
+}
+
+impl<T: Residue> Div<T> for ModInt {
+    type Output = Self;
+
+    /// Multiply by the inverse. Panics if `rhs` has no inverse.
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: T) -> Self::Output {
+        let rhs = Self::new(rhs, self.modulus);
+        match rhs.inverse() {
+            Some(inverse) => self * inverse,
+            None => panic!("{rhs} has no inverse modulo {}", self.modulus),
+        }
+    }
+}
+
+macro_rules! assign_ops {
+    ($(($trait:ident, $fn:ident, $op:tt)),*) => {$(
+        impl<T: Residue> $trait<T> for ModInt {
+            fn $fn(&mut self, rhs: T) {
+                *self = *self $op rhs;
+            }
+        }
+    )*};
+}
+
+assign_ops!(
+    (AddAssign, add_assign, +),
+    (SubAssign, sub_assign, -),
+    (MulAssign, mul_assign, *),
+    (DivAssign, div_assign, /)
+);
+
+// Integers on the left hand side, e.g. `2 * x`.
+macro_rules! int_lhs {
+    ($($t:ty),*) => {$(
+        impl Add<ModInt> for $t {
+            type Output = ModInt;
+
+            fn add(self, rhs: ModInt) -> Self::Output {
+                rhs + self
+            }
+        }
+
+        impl Sub<ModInt> for $t {
+            type Output = ModInt;
+
+            fn sub(self, rhs: ModInt) -> Self::Output {
+                ModInt::new(self, rhs.modulus) - rhs
+            }
+        }
+
+        impl Mul<ModInt> for $t {
+            type Output = ModInt;
+
+            fn mul(self, rhs: ModInt) -> Self::Output {
+                rhs * self
+            }
+        }
+
+        impl Div<ModInt> for $t {
+            type Output = ModInt;
+
+            fn div(self, rhs: ModInt) -> Self::Output {
+                ModInt::new(self, rhs.modulus) / rhs
+            }
+        }
+    )*};
+}
+
+int_lhs!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduce() {
+        assert_eq!(2, ModInt::new(12, 5).value());
+        assert_eq!(3, ModInt::new(-12, 5).value());
+        assert_eq!(0, ModInt::new(u64::MAX, 3).value());
+    }
+
+    #[test]
+    fn test_ops() {
+        let x = ModInt::new(4, 7);
+        assert_eq!(ModInt::new(1, 7), x + 4);
+        assert_eq!(ModInt::new(5, 7), x - 6);
+        assert_eq!(ModInt::new(2, 7), x * 4_u64);
+        assert_eq!(ModInt::new(3, 7), -x);
+        assert_eq!(ModInt::new(2, 7), 3 - x * 2);
+        assert_eq!(ModInt::new(4, 7), x / 8);
+    }
+
+    #[test]
+    fn test_no_overflow() {
+        let big = ModInt::new(u64::MAX - 1, u64::MAX);
+        assert_eq!(1, (big * big).value());
+        assert_eq!(u64::MAX - 2, (big + big).value());
+    }
+
+    #[test]
+    fn test_pow_and_inverse() {
+        let x = ModInt::new(3, 1_000_000_007);
+        assert_eq!(ModInt::new(59049, 1_000_000_007), x.pow(10));
+        assert_eq!(1, (x * x.inverse().unwrap()).value());
+        assert_eq!(None, ModInt::new(4, 10).inverse());
+        assert_eq!(Some(ModInt::new(7, 10)), ModInt::new(3, 10).inverse());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mixed_moduli() {
+        let _ = ModInt::new(1, 5) + ModInt::new(1, 7);
+    }
+}
@@ -0,0 +1,129 @@
+//! Run independent work on several threads with results that don't depend on how many.
+
+use std::{
+    iter::Sum,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+/// Environment variable read for the worker count when none has been set.
+pub const THREADS_ENV: &str = "EBC_THREADS";
+
+static WORKERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of threads the helpers here use. Set by [`set_workers`], otherwise taken from
+/// `EBC_THREADS`, otherwise the number of available cores.
+pub fn workers() -> usize {
+    match WORKERS.load(Ordering::Relaxed) {
+        0 => std::env::var(THREADS_ENV)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get())),
+        n => n,
+    }
+}
+
+/// Use `workers` threads from now on. Zero goes back to the default.
+pub fn set_workers(workers: usize) {
+    WORKERS.store(workers, Ordering::Relaxed);
+}
+
+/// `f` applied to every item, in the same order as the items. Each thread takes one
+/// contiguous run of the slice.
+pub fn par_map<T, R>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+{
+    par_map_with(workers(), items, f)
+}
+
+/// [`par_map`] on `workers` threads rather than the usual number.
+pub fn par_map_with<T, R>(workers: usize, items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+{
+    let workers = workers.min(items.len()).max(1);
+    if workers == 1 {
+        return items.iter().map(f).collect();
+    }
+    let size = items.len().div_ceil(workers);
+    thread::scope(|scope| {
+        let handles = items
+            .chunks(size)
+            .map(|chunk| scope.spawn(|| chunk.iter().map(&f).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    })
+}
+
+/// [`par_map`] over anything iterable, collecting it first.
+pub fn par_map_iter<I, R>(items: I, f: impl Fn(&I::Item) -> R + Sync) -> Vec<R>
+where
+    I: IntoIterator,
+    I::Item: Sync,
+    R: Send,
+{
+    par_map(&items.into_iter().collect::<Vec<_>>(), f)
+}
+
+/// Sum of `f` over every item. The results are added up in item order so even float
+/// sums come out the same for any number of threads.
+pub fn par_sum<T, R>(items: &[T], f: impl Fn(&T) -> R + Sync) -> R
+where
+    T: Sync,
+    R: Send + Sum,
+{
+    par_map(items, f).into_iter().sum()
+}
+
+/// Smallest result of `f`, or `None` with no items.
+pub fn par_min<T, R>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Option<R>
+where
+    T: Sync,
+    R: Send + Ord,
+{
+    par_map(items, f).into_iter().min()
+}
+
+/// Largest result of `f`, or `None` with no items.
+pub fn par_max<T, R>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Option<R>
+where
+    T: Sync,
+    R: Send + Ord,
+{
+    par_map(items, f).into_iter().max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_for_any_worker_count() {
+        let items = (0..1000_u64).collect::<Vec<_>>();
+        let expected = items.iter().map(|v| v * v).collect::<Vec<_>>();
+        let floats = (0..1000)
+            .map(|v| 1.0 / (v as f64 + 1.0))
+            .collect::<Vec<_>>();
+        let float_sum = floats.iter().sum::<f64>();
+        for workers in [0, 1, 2, 3, 8, 2000] {
+            assert_eq!(expected, par_map_with(workers, &items, |v| v * v));
+            let sum = par_map_with(workers, &floats, |v| *v)
+                .into_iter()
+                .sum::<f64>();
+            assert_eq!(float_sum, sum);
+        }
+        assert_eq!(expected, par_map(&items, |v| v * v));
+        assert_eq!(float_sum, par_sum(&floats, |v| *v));
+        assert_eq!(Some(3), par_min(&items, |v| v.abs_diff(3) + 3));
+        assert_eq!(Some(999), par_max(&items, |v| *v));
+        assert_eq!(vec![2, 4], par_map_iter(1..=2, |v| v * 2));
+        assert_eq!(None, par_min(&[] as &[u64], |v| *v));
+    }
+}
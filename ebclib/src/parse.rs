@@ -0,0 +1,509 @@
+//! Reading puzzle input with errors that point at the bad text.
+//!
+//! A [`Source`] hands out a [`Span`] for each line. Splitting or parsing a span keeps
+//! track of where it came from, so a [`ParseError`] can show the line with a caret
+//! under the offending part.
+
+use std::{any::type_name, fmt, fs, str::FromStr};
+
+/// Where in the input an error is.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Location {
+    /// Line number, counting from 1.
+    pub line: usize,
+    /// Column in characters, counting from 1.
+    pub column: usize,
+    /// Characters to underline.
+    pub len: usize,
+    /// The whole line.
+    pub text: String,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub file: Option<String>,
+    /// `None` when the problem is with the input as a whole.
+    pub location: Option<Location>,
+    pub expected: String,
+}
+
+impl ParseError {
+    /// An error about the input as a whole.
+    pub fn new(expected: impl Into<String>) -> Self {
+        Self {
+            file: None,
+            location: None,
+            expected: expected.into(),
+        }
+    }
+
+    /// Name the file the error is in, if it isn't already.
+    pub fn in_file(mut self, file: &str) -> Self {
+        self.file.get_or_insert_with(|| file.to_string());
+        self
+    }
+
+    /// The bad line with the bad part underlined, or nothing without a location.
+    pub fn snippet(&self) -> String {
+        let Some(at) = self.location.as_ref() else {
+            return String::new();
+        };
+        let number = at.line.to_string();
+        let pad = " ".repeat(number.len());
+        format!(
+            "{pad} |\n{number} | {}\n{pad} | {}{}",
+            at.text,
+            " ".repeat(at.column - 1),
+            "^".repeat(at.len.max(1))
+        )
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let file = self.file.as_deref().unwrap_or("<input>");
+        match self.location.as_ref() {
+            Some(at) => write!(
+                f,
+                "{file}:{}:{}: expected {}\n{}",
+                at.line,
+                at.column,
+                self.expected,
+                self.snippet()
+            ),
+            None => write!(f, "{file}: expected {}", self.expected),
+        }
+    }
+}
+
+/// Shows the snippet too, so a `main` returning this error prints it readably.
+impl fmt::Debug for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<ParseError> for String {
+    fn from(value: ParseError) -> Self {
+        value.to_string()
+    }
+}
+
+/// Part of one line of input, remembering where it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span<'a> {
+    file: Option<&'a str>,
+    line: usize,
+    full: &'a str,
+    start: usize,
+    text: &'a str,
+}
+
+impl<'a> Span<'a> {
+    /// A span covering `text` as line 1 of an unnamed input.
+    pub fn new(text: &'a str) -> Self {
+        Self::line(None, 1, text)
+    }
+
+    fn line(file: Option<&'a str>, line: usize, full: &'a str) -> Self {
+        Self {
+            file,
+            line,
+            full,
+            start: 0,
+            text: full,
+        }
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Line number, counting from 1.
+    pub fn line_number(&self) -> usize {
+        self.line
+    }
+
+    /// An error underlining this span.
+    pub fn error(&self, expected: impl Into<String>) -> ParseError {
+        ParseError {
+            file: self.file.map(str::to_string),
+            location: Some(Location {
+                line: self.line,
+                column: self.full[..self.start].chars().count() + 1,
+                len: self.text.chars().count(),
+                text: self.full.to_string(),
+            }),
+            expected: expected.into(),
+        }
+    }
+
+    /// Parse the whole span, naming the type expected if it doesn't parse.
+    pub fn parse<T>(&self) -> Result<T, ParseError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.text
+            .parse()
+            .map_err(|e| self.error(format!("{} ({e})", short_name::<T>())))
+    }
+
+    /// The span from byte `start` to `end`.
+    fn sub(&self, start: usize, end: usize) -> Self {
+        Self {
+            start: self.start + start,
+            text: &self.text[start..end],
+            ..*self
+        }
+    }
+
+    pub fn trim(&self) -> Self {
+        let start = self.text.len() - self.text.trim_start().len();
+        let end = self.text.trim_end().len();
+        self.sub(start, end.max(start))
+    }
+
+    /// Split at byte `mid`, which must be on a char boundary.
+    pub fn split_at(&self, mid: usize) -> (Self, Self) {
+        (self.sub(0, mid), self.sub(mid, self.text.len()))
+    }
+
+    /// The parts either side of the first `sep`, or an error just after the span.
+    pub fn split_once(&self, sep: char) -> Result<(Self, Self), ParseError> {
+        let Some(idx) = self.text.find(sep) else {
+            return Err(self
+                .sub(self.text.len(), self.text.len())
+                .error(format!("{sep:?}")));
+        };
+        Ok((
+            self.sub(0, idx),
+            self.sub(idx + sep.len_utf8(), self.text.len()),
+        ))
+    }
+
+    /// Every part between `sep`s.
+    pub fn split(&self, sep: char) -> impl Iterator<Item = Span<'a>> + use<'a> {
+        let span = *self;
+        let mut start = 0;
+        span.text
+            .match_indices(sep)
+            .map(|(idx, _)| idx)
+            .chain(std::iter::once(span.text.len()))
+            .map(move |end| {
+                let part = span.sub(start, end);
+                start = end + sep.len_utf8();
+                part
+            })
+    }
+
+    /// Every run of non-whitespace.
+    pub fn words(&self) -> impl Iterator<Item = Span<'a>> + use<'a> {
+        let span = *self;
+        span.text.split_whitespace().map(move |word| {
+            let start = word.as_ptr() as usize - span.text.as_ptr() as usize;
+            span.sub(start, start + word.len())
+        })
+    }
+
+    /// Whitespace separated values, like `2 3 4 5`.
+    pub fn numbers<T>(&self) -> Result<Vec<T>, ParseError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.words().map(|word| word.parse()).collect()
+    }
+
+    /// `sep` separated values, like `U5,R3` or `1, 2`, each trimmed.
+    pub fn list<T>(&self, sep: char) -> Result<Vec<T>, ParseError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.split(sep).map(|part| part.trim().parse()).collect()
+    }
+
+    /// A `KEY:a,b,c` note as the key and its non-empty values, all trimmed.
+    pub fn keyed(&self) -> Result<(Span<'a>, Vec<Span<'a>>), ParseError> {
+        let (key, values) = self.split_once(':')?;
+        let values = values
+            .split(',')
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+            .collect();
+        Ok((key.trim(), values))
+    }
+
+    /// A letter then a value, like `U5`.
+    pub fn tagged<T>(&self) -> Result<(char, T), ParseError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let span = self.trim();
+        let Some(letter) = span.text.chars().next() else {
+            return Err(span.error("a letter then a value"));
+        };
+        let (_, value) = span.split_at(letter.len_utf8());
+        Ok((letter, value.parse()?))
+    }
+
+    /// Whitespace separated fields as a tuple, like `(usize, usize)` from `3 4`.
+    pub fn tuple<T: Fields>(&self) -> Result<T, ParseError> {
+        T::from_fields(self, &self.words().collect::<Vec<_>>())
+    }
+
+    /// Every character as a symbol, like those from `#[derive(Symbol)]`.
+    pub fn symbols<T: TryFrom<char, Error = String>>(&self) -> Result<Vec<T>, ParseError> {
+        self.text
+            .char_indices()
+            .map(|(idx, ch)| {
+                T::try_from(ch).map_err(|e| {
+                    let at = self.sub(idx, idx + ch.len_utf8());
+                    at.error(format!("{} ({e})", short_name::<T>()))
+                })
+            })
+            .collect()
+    }
+
+    /// Blocks `width` characters wide with `gap` characters between them, `None` for
+    /// blocks that are blank. A short last block is kept as it is. Panics if `width` is
+    /// zero.
+    pub fn blocks(
+        &self,
+        width: usize,
+        gap: usize,
+    ) -> impl Iterator<Item = Option<Span<'a>>> + use<'a> {
+        assert!(width > 0, "Blocks need a width of at least 1");
+        let span = *self;
+        let bounds = span
+            .text
+            .char_indices()
+            .map(|(idx, _)| idx)
+            .chain(std::iter::once(span.text.len()))
+            .collect::<Vec<_>>();
+        let chars = bounds.len() - 1;
+        (0..chars).step_by(width + gap).map(move |first| {
+            let block = span.sub(bounds[first], bounds[(first + width).min(chars)]);
+            (!block.trim().is_empty()).then_some(block)
+        })
+    }
+}
+
+/// Tuples that can be parsed a field at a time, see [`Span::tuple`].
+pub trait Fields: Sized {
+    fn from_fields(span: &Span, fields: &[Span]) -> Result<Self, ParseError>;
+}
+
+macro_rules! fields {
+    ($len:literal; $($t:ident $idx:tt),+) => {
+        impl<$($t),+> Fields for ($($t,)+)
+        where
+            $($t: FromStr, $t::Err: fmt::Display),+
+        {
+            fn from_fields(span: &Span, fields: &[Span]) -> Result<Self, ParseError> {
+                if fields.len() != $len {
+                    return Err(span.error(concat!($len, " fields")));
+                }
+                Ok(($(fields[$idx].parse()?,)+))
+            }
+        }
+    };
+}
+
+fields!(2; A 0, B 1);
+fields!(3; A 0, B 1, C 2);
+fields!(4; A 0, B 1, C 2, D 3);
+
+/// The last part of a type's path, e.g. `i64` or `Step`.
+fn short_name<T>() -> &'static str {
+    type_name::<T>().rsplit("::").next().unwrap_or_default()
+}
+
+/// Every line of `lines` that isn't blank, numbered by its place in the whole slice.
+pub fn lines<S: AsRef<str>>(lines: &[S]) -> impl Iterator<Item = Span<'_>> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| Span::line(None, idx + 1, line.as_ref()))
+        .filter(|span| !span.text.trim().is_empty())
+}
+
+/// Runs of lines separated by blank ones.
+pub fn sections<S: AsRef<str>>(lines: &[S]) -> Vec<Vec<Span<'_>>> {
+    let mut sections = vec![];
+    let mut section = vec![];
+    for (idx, line) in lines.iter().enumerate() {
+        let span = Span::line(None, idx + 1, line.as_ref());
+        if !span.text.trim().is_empty() {
+            section.push(span);
+        } else if !section.is_empty() {
+            sections.push(std::mem::take(&mut section));
+        }
+    }
+    if !section.is_empty() {
+        sections.push(section);
+    }
+    sections
+}
+
+/// A header line and every line after it that isn't blank, like a key and a grid.
+pub fn headed<S: AsRef<str>>(lines: &[S]) -> Result<(Span<'_>, Vec<Span<'_>>), ParseError> {
+    let mut lines = self::lines(lines);
+    let header = lines
+        .next()
+        .ok_or_else(|| ParseError::new("a header line"))?;
+    Ok((header, lines.collect()))
+}
+
+/// The text of an input file.
+#[derive(Debug, Clone)]
+pub struct Source {
+    file: Option<String>,
+    text: String,
+}
+
+impl Source {
+    pub fn read(path: &str) -> Result<Self, ParseError> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| ParseError::new(format!("a readable file ({e})")).in_file(path))?;
+        Ok(Self {
+            file: Some(path.to_string()),
+            text,
+        })
+    }
+
+    /// Input that didn't come from a file, like a puzzle example.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            file: None,
+            text: text.into(),
+        }
+    }
+
+    /// An error about the input as a whole.
+    pub fn error(&self, expected: impl Into<String>) -> ParseError {
+        let error = ParseError::new(expected);
+        match self.file.as_deref() {
+            Some(file) => error.in_file(file),
+            None => error,
+        }
+    }
+
+    /// Every line that isn't blank.
+    pub fn lines(&self) -> impl Iterator<Item = Span<'_>> {
+        let file = self.file.as_deref();
+        self.text
+            .lines()
+            .enumerate()
+            .map(move |(idx, line)| Span::line(file, idx + 1, line))
+            .filter(|span| !span.text.trim().is_empty())
+    }
+
+    /// Run a parser over every line, blank ones included, naming the file in any error.
+    pub fn parse_with<'a, T>(
+        &'a self,
+        parser: impl FnOnce(&[&'a str]) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        let lines = self.text.lines().collect::<Vec<_>>();
+        match (parser(&lines), self.file.as_deref()) {
+            (Err(e), Some(file)) => Err(e.in_file(file)),
+            (result, _) => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spans() {
+        let source = Source::new("A: 1, 2\n\n B:3,x ");
+        let lines = source.lines().collect::<Vec<_>>();
+        assert_eq!(2, lines.len());
+        let (name, values) = lines[1].trim().split_once(':').unwrap();
+        assert_eq!("B", name.as_str());
+        let values = values.split(',').map(|v| v.as_str()).collect::<Vec<_>>();
+        assert_eq!(vec!["3", "x"], values);
+        let err = lines[1]
+            .split(',')
+            .nth(1)
+            .unwrap()
+            .trim()
+            .parse::<i64>()
+            .unwrap_err();
+        let at = err.location.as_ref().unwrap();
+        assert_eq!((3, 6, 1), (at.line, at.column, at.len));
+        assert_eq!(
+            "<input>:3:6: expected i64 (invalid digit found in string)\n  |\n3 |  B:3,x \n  |      ^",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_helpers() {
+        let (key, values) = Span::new("WORDS: THE,OWE,,MES").keyed().unwrap();
+        assert_eq!("WORDS", key.as_str());
+        assert_eq!(
+            vec!["THE", "OWE", "MES"],
+            values.iter().map(|v| v.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(Ok(vec![2, 3, 45]), Span::new(" 2 3  45").numbers::<u32>());
+        assert_eq!(Ok(vec![1, 2, 3]), Span::new("1, 2,3").list::<i64>(','));
+        assert_eq!(Ok(('U', 15)), Span::new("U15").tagged::<i64>());
+        assert_eq!(Ok((17, 5)), Span::new("17 5").tuple::<(usize, i64)>());
+        let err = Span::new("17 5 3").tuple::<(usize, i64)>().unwrap_err();
+        assert_eq!("2 fields", err.expected);
+        let blocks = Span::new("^_^     >.<")
+            .blocks(3, 1)
+            .map(|b| b.map(|b| b.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![Some("^_^"), None, Some(">.<")], blocks);
+    }
+
+    #[test]
+    #[should_panic(expected = "width of at least 1")]
+    fn test_zero_width_blocks() {
+        let _ = Span::new("^_^").blocks(0, 1);
+    }
+
+    #[test]
+    fn test_sections() {
+        let lines = ["LR", "", "", ">A.", ".B<", "", "C"];
+        let sections = sections(&lines);
+        let sizes = sections.iter().map(|s| s.len()).collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 1], sizes);
+        assert_eq!(5, sections[1][1].line_number());
+        let (header, body) = headed(&lines).unwrap();
+        assert_eq!(("LR", 3), (header.as_str(), body.len()));
+        assert!(headed(&[""]).is_err());
+    }
+
+    #[test]
+    fn test_missing_separator() {
+        let err = Span::new("AB")
+            .split_once(':')
+            .unwrap_err()
+            .in_file("notes.txt");
+        assert_eq!(
+            "notes.txt:1:3: expected ':'\n  |\n1 | AB\n  |   ^",
+            err.to_string()
+        );
+        let err = Source::new("\nA")
+            .parse_with::<()>(|lines| Err(self::lines(lines).next().unwrap().error("a note")))
+            .unwrap_err();
+        assert_eq!(2, err.location.unwrap().line);
+        assert_eq!(
+            "<input>: expected a root",
+            ParseError::new("a root").to_string()
+        );
+    }
+}
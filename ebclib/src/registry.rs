@@ -0,0 +1,49 @@
+//! Quests a runner knows about, found from `#[ebclib::quest]` attributes.
+//!
+//! A quest is registered as the binary that solves it, not as separate part functions.
+//! Each quest's parts take their own parsed input, so the runner runs the binary and
+//! it prints every part.
+
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct QuestId {
+    pub event: &'static str,
+    pub year: u16,
+    pub number: u8,
+}
+
+impl QuestId {
+    /// Name of the binary holding the quest, e.g. `koa2024q07`.
+    pub fn bin(&self) -> String {
+        format!("{}{}q{:02}", self.event, self.year, self.number)
+    }
+}
+
+impl Display for QuestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} Quest {:02}",
+            self.event.to_uppercase(),
+            self.year,
+            self.number
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        let quest = QuestId {
+            event: "koa",
+            year: 2024,
+            number: 7,
+        };
+        assert_eq!("koa2024q07", quest.bin());
+        assert_eq!("KOA 2024 Quest 07", quest.to_string());
+    }
+}
@@ -0,0 +1,321 @@
+//! Pictures of grids and simulations written straight to image files.
+//!
+//! Grids become an [`Image`] through a colour for each cell, saved as PPM or PNG, or
+//! drawn as SVG. An [`Animation`] collects a picture per step and saves a looping GIF
+//! or a numbered file per frame.
+
+mod gif;
+mod png;
+
+use std::{collections::HashMap, fmt::Write, fs, path::Path};
+
+use crate::grid::Grid;
+
+/// Environment variable naming a directory for quests to draw their working into.
+pub const RENDER_ENV: &str = "EBC_RENDER";
+
+pub type Rgb = [u8; 3];
+
+pub const BLACK: Rgb = [0, 0, 0];
+pub const WHITE: Rgb = [255, 255, 255];
+
+/// Colours from white through blue to black for `value` out of `max`, handy for heights
+/// and distances.
+pub fn shade(value: usize, max: usize) -> Rgb {
+    let t = value.min(max) as f64 / max.max(1) as f64;
+    let fade = |from: f64| (from * (1.0 - t)) as u8;
+    [
+        fade(255.0),
+        fade(255.0 - 90.0 * t),
+        255 - (200.0 * t * t) as u8,
+    ]
+}
+
+/// The directory named by `EBC_RENDER`, if it's set.
+pub fn render_dir() -> Option<std::path::PathBuf> {
+    std::env::var_os(RENDER_ENV).map(Into::into)
+}
+
+/// An RGB picture stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, fill: Rgb) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![fill; width * height],
+        }
+    }
+
+    /// Every cell of `grid` as a `scale` by `scale` square of its colour.
+    pub fn from_grid<T>(grid: &Grid<T>, scale: usize, colour: impl Fn(&T) -> Rgb) -> Self {
+        let mut image = Self::new(grid.cols() * scale, grid.rows() * scale, BLACK);
+        for ((row, col), cell) in grid.iter() {
+            let rgb = colour(cell);
+            for y in row * scale..(row + 1) * scale {
+                image.pixels[y * image.width + col * scale..][..scale].fill(rgb);
+            }
+        }
+        image
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Rgb> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, rgb: Rgb) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = rgb;
+        }
+    }
+
+    /// Binary PPM (`P6`).
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        ppm.extend(self.pixels.iter().flatten());
+        ppm
+    }
+
+    /// A PNG of the image, which must have at least one pixel.
+    pub fn to_png(&self) -> Result<Vec<u8>, String> {
+        if self.width == 0 || self.height == 0 {
+            return Err(format!(
+                "{}x{} is too small for a PNG",
+                self.width, self.height
+            ));
+        }
+        if u32::try_from(self.width).is_err() || u32::try_from(self.height).is_err() {
+            return Err(format!(
+                "{}x{} is too big for a PNG",
+                self.width, self.height
+            ));
+        }
+        Ok(png::encode(
+            self.width,
+            self.height,
+            self.pixels.as_flattened(),
+        ))
+    }
+
+    /// Save as PNG or PPM, picked by the file's extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let bytes = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("png") => self.to_png()?,
+            Some("ppm") => self.to_ppm(),
+            _ => return Err(format!("Can't tell the format of {}", path.display())),
+        };
+        fs::write(path, bytes).map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
+
+/// An SVG of `grid` with a `cell` pixel square for each cell, and `label` text on any
+/// cell it gives some for.
+pub fn grid_svg<T>(
+    grid: &Grid<T>,
+    cell: usize,
+    colour: impl Fn(&T) -> Rgb,
+    label: impl Fn(&T) -> Option<String>,
+) -> String {
+    let (width, height) = (grid.cols() * cell, grid.rows() * cell);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\" shape-rendering=\"crispEdges\">\n"
+    );
+    for ((row, col), value) in grid.iter() {
+        let [r, g, b] = colour(value);
+        let (x, y) = (col * cell, row * cell);
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{x}\" y=\"{y}\" width=\"{cell}\" height=\"{cell}\" \
+             fill=\"#{r:02x}{g:02x}{b:02x}\"/>"
+        );
+        if let Some(text) = label(value) {
+            let text = text
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;");
+            let _ = writeln!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" font-size=\"{}\" text-anchor=\"middle\" \
+                 dominant-baseline=\"central\">{text}</text>",
+                x + cell / 2,
+                y + cell / 2,
+                cell * 3 / 4
+            );
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Frames of a simulation, all the same size.
+#[derive(Debug, Clone, Default)]
+pub struct Animation {
+    frames: Vec<Image>,
+}
+
+impl Animation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, frame: Image) {
+        self.frames.push(frame);
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// A looping GIF showing each frame for `delay` hundredths of a second. Fails if
+    /// the frames differ in size or use more than 256 colours between them.
+    pub fn to_gif(&self, delay: u16) -> Result<Vec<u8>, String> {
+        let Some(first) = self.frames.first() else {
+            return Err("No frames to animate".to_string());
+        };
+        let (width, height) = (first.width, first.height);
+        if let Some(idx) = self
+            .frames
+            .iter()
+            .position(|f| (f.width, f.height) != (width, height))
+        {
+            return Err(format!("Frame {idx} is a different size to frame 0"));
+        }
+        if width == 0 || height == 0 {
+            return Err(format!("{width}x{height} is too small for a GIF"));
+        }
+        let (Ok(gif_width), Ok(gif_height)) = (u16::try_from(width), u16::try_from(height)) else {
+            return Err(format!("{width}x{height} is too big for a GIF"));
+        };
+        let mut palette = vec![];
+        let mut lookup: HashMap<Rgb, u8> = HashMap::new();
+        let mut frames = vec![];
+        for frame in self.frames.iter() {
+            let mut indexes = Vec::with_capacity(frame.pixels.len());
+            for rgb in frame.pixels.iter() {
+                let index = match lookup.get(rgb) {
+                    Some(index) => *index,
+                    None if palette.len() < 256 => {
+                        palette.push(*rgb);
+                        lookup.insert(*rgb, (palette.len() - 1) as u8);
+                        (palette.len() - 1) as u8
+                    }
+                    None => return Err("More than 256 colours".to_string()),
+                };
+                indexes.push(index);
+            }
+            frames.push(indexes);
+        }
+        Ok(gif::encode(gif_width, gif_height, &palette, &frames, delay))
+    }
+
+    pub fn save_gif(&self, path: impl AsRef<Path>, delay: u16) -> Result<(), String> {
+        let path = path.as_ref();
+        fs::write(path, self.to_gif(delay)?).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Save every frame into `dir` as `{prefix}0001.{ext}` and so on, with `ext` one of
+    /// `png` or `ppm`.
+    pub fn save_frames(
+        &self,
+        dir: impl AsRef<Path>,
+        prefix: &str,
+        ext: &str,
+    ) -> Result<(), String> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+        for (idx, frame) in self.frames.iter().enumerate() {
+            frame.save(dir.join(format!("{prefix}{:04}.{ext}", idx + 1)))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_grid() {
+        let grid = Grid::from_rows(vec![vec![true, false], vec![false, false]]);
+        let image = Image::from_grid(&grid, 2, |on| if *on { WHITE } else { BLACK });
+        assert_eq!((4, 4), (image.width(), image.height()));
+        assert_eq!(Some(WHITE), image.get(1, 1));
+        assert_eq!(Some(BLACK), image.get(2, 1));
+        assert_eq!(None, image.get(4, 0));
+        let ppm = image.to_ppm();
+        assert!(ppm.starts_with(b"P6\n4 4\n255\n"));
+        assert_eq!(11 + 4 * 4 * 3, ppm.len());
+    }
+
+    #[test]
+    fn test_png() {
+        assert!(
+            Image::new(2, 3, WHITE)
+                .to_png()
+                .unwrap()
+                .starts_with(b"\x89PNG")
+        );
+        assert_eq!(
+            Err("0x3 is too small for a PNG".to_string()),
+            Image::new(0, 3, BLACK).to_png()
+        );
+        assert!(Image::new(3, 0, BLACK).to_png().is_err());
+        let mut empty = Animation::new();
+        empty.push(Image::new(0, 3, BLACK));
+        assert_eq!(
+            Err("0x3 is too small for a GIF".to_string()),
+            empty.to_gif(5)
+        );
+    }
+
+    #[test]
+    fn test_svg() {
+        let grid = Grid::from_rows(vec![vec![1, 2]]);
+        let svg = grid_svg(&grid, 10, |n| shade(*n, 2), |n| Some(format!("<{n}")));
+        assert_eq!(2, svg.matches("<rect").count());
+        assert!(svg.contains("x=\"10\" y=\"0\""));
+        assert!(svg.contains("&lt;2</text>"));
+    }
+
+    #[test]
+    fn test_animation() {
+        let mut animation = Animation::new();
+        assert!(animation.to_gif(5).is_err());
+        animation.push(Image::new(3, 2, WHITE));
+        animation.push(Image::new(3, 2, shade(1, 3)));
+        assert!(animation.to_gif(5).unwrap().starts_with(b"GIF89a"));
+        animation.push(Image::new(2, 2, WHITE));
+        assert_eq!(
+            Err("Frame 2 is a different size to frame 0".to_string()),
+            animation.to_gif(5)
+        );
+        let mut colourful = Animation::new();
+        let mut image = Image::new(300, 1, BLACK);
+        (0..300).for_each(|x| image.set(x, 0, [x as u8, (x / 256) as u8, 0]));
+        colourful.push(image);
+        assert_eq!(
+            Err("More than 256 colours".to_string()),
+            colourful.to_gif(5)
+        );
+    }
+}
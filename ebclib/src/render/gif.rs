@@ -0,0 +1,197 @@
+//! Animated GIFs from frames of palette indexes, compressed with LZW.
+
+const MAX_CODES: u16 = 4096;
+
+/// Bytes of a looping GIF. `palette` has at most 256 colours and every frame holds
+/// `width * height` indexes into it. `delay` is in hundredths of a second.
+pub(super) fn encode(
+    width: u16,
+    height: u16,
+    palette: &[[u8; 3]],
+    frames: &[Vec<u8>],
+    delay: u16,
+) -> Vec<u8> {
+    // The colour table holds a power of two colours, at least 4.
+    let bits = palette.len().next_power_of_two().trailing_zeros().max(2) as u8;
+    let mut gif = b"GIF89a".to_vec();
+    gif.extend(width.to_le_bytes());
+    gif.extend(height.to_le_bytes());
+    gif.extend([0x80 | 0x70 | (bits - 1), 0, 0]);
+    for idx in 0..1 << bits {
+        gif.extend(palette.get(idx).unwrap_or(&[0, 0, 0]));
+    }
+    // Loop forever.
+    gif.extend([0x21, 0xff, 0x0b]);
+    gif.extend(b"NETSCAPE2.0");
+    gif.extend([3, 1, 0, 0, 0]);
+
+    for frame in frames {
+        gif.extend([0x21, 0xf9, 4, 0]);
+        gif.extend(delay.to_le_bytes());
+        gif.extend([0, 0]);
+        gif.push(0x2c);
+        gif.extend([0, 0, 0, 0]);
+        gif.extend(width.to_le_bytes());
+        gif.extend(height.to_le_bytes());
+        gif.push(0);
+        gif.push(bits);
+        for block in lzw(bits, frame).chunks(255) {
+            gif.push(block.len() as u8);
+            gif.extend(block);
+        }
+        gif.push(0);
+    }
+    gif.push(0x3b);
+    gif
+}
+
+/// Codes packed least significant bit first.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u32,
+    used: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.acc |= (code as u32) << self.used;
+        self.used += size as u32;
+        while self.used >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.used -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.used > 0 {
+            self.bytes.push(self.acc as u8);
+        }
+        self.bytes
+    }
+}
+
+fn lzw(min_size: u8, indexes: &[u8]) -> Vec<u8> {
+    let clear = 1_u16 << min_size;
+    let end = clear + 1;
+    let mut out = BitWriter::default();
+    let mut table = std::collections::HashMap::new();
+    let mut size = min_size + 1;
+    let mut next = end + 1;
+    out.write(clear, size);
+    let Some((first, rest)) = indexes.split_first() else {
+        out.write(end, size);
+        return out.finish();
+    };
+    let mut current = *first as u16;
+    for index in rest {
+        if let Some(code) = table.get(&(current, *index)) {
+            current = *code;
+            continue;
+        }
+        out.write(current, size);
+        if next < MAX_CODES {
+            table.insert((current, *index), next);
+            // The decoder adds this code a step later, so it widens when it sees the
+            // code after next.
+            if next == 1 << size && size < 12 {
+                size += 1;
+            }
+            next += 1;
+        } else {
+            out.write(clear, size);
+            table.clear();
+            size = min_size + 1;
+            next = end + 1;
+        }
+        current = *index as u16;
+    }
+    out.write(current, size);
+    if next == 1 << size && size < 12 {
+        size += 1;
+    }
+    out.write(end, size);
+    out.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A plain LZW decoder to check the encoder against.
+    fn unlzw(min_size: u8, bytes: &[u8]) -> Vec<u8> {
+        let clear = 1_usize << min_size;
+        let mut table: Vec<Vec<u8>> = vec![];
+        let mut size = min_size + 1;
+        let mut prev: Option<usize> = None;
+        let (mut acc, mut used, mut bytes) = (0_u32, 0, bytes.iter());
+        let mut out = vec![];
+        loop {
+            while used < size as u32 {
+                acc |= (*bytes.next().unwrap() as u32) << used;
+                used += 8;
+            }
+            let code = (acc & ((1 << size) - 1)) as usize;
+            acc >>= size;
+            used -= size as u32;
+            if code == clear {
+                table = (0..clear + 2).map(|c| vec![c as u8]).collect();
+                size = min_size + 1;
+                prev = None;
+                continue;
+            }
+            if code == clear + 1 {
+                return out;
+            }
+            let entry = match (table.get(code), prev) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(prev)) => {
+                    let mut entry = table[prev].clone();
+                    entry.push(table[prev][0]);
+                    entry
+                }
+                (None, None) => panic!("Bad first code {code}"),
+            };
+            if let Some(prev) = prev
+                && table.len() < 4096
+            {
+                let mut added = table[prev].clone();
+                added.push(entry[0]);
+                table.push(added);
+                if table.len() == 1 << size && size < 12 {
+                    size += 1;
+                }
+            }
+            out.extend(&entry);
+            prev = Some(code);
+        }
+    }
+
+    #[test]
+    fn test_lzw_round_trip() {
+        let mut seed = 7_u32;
+        for (bits, len) in [(2, 0), (2, 1), (2, 300), (3, 5000), (8, 20000)] {
+            let indexes = (0..len)
+                .map(|i| {
+                    seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                    // Runs make the table grow long entries as well as many.
+                    if i % 7 < 4 {
+                        1
+                    } else {
+                        ((seed >> 16) % (1 << bits)) as u8
+                    }
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(indexes, unlzw(bits, &lzw(bits, &indexes)), "{bits} {len}");
+        }
+    }
+
+    #[test]
+    fn test_header() {
+        let gif = encode(2, 1, &[[0, 0, 0], [255, 255, 255]], &[vec![0, 1]], 10);
+        assert_eq!(b"GIF89a", &gif[..6]);
+        assert_eq!([2, 0, 1, 0], gif[6..10]);
+        assert_eq!(Some(&0x3b), gif.last());
+    }
+}
@@ -0,0 +1,234 @@
+//! Circular sequences indexed by any integer.
+
+use std::ops::Index;
+
+/// A sequence whose ends join up. Rotating only moves an offset, the items stay put.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Ring<T> {
+    items: Vec<T>,
+    offset: usize,
+}
+
+/// A borrowed, rotated view of a [`Ring`]. Cheap to copy and rotate further.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct RingView<'a, T> {
+    items: &'a [T],
+    offset: usize,
+}
+
+impl<T> Clone for RingView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for RingView<'_, T> {}
+
+impl<T> From<Vec<T>> for Ring<T> {
+    fn from(items: Vec<T>) -> Self {
+        Self { items, offset: 0 }
+    }
+}
+
+impl<T> FromIterator<T> for Ring<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<T> Ring<T> {
+    pub fn new() -> Self {
+        Self::from(vec![])
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.items.insert(self.offset, item);
+        self.offset += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Where index 0 currently is in the original order.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Shift index 0 forward by `by`, so what was at index `by` is now at 0. Negative
+    /// values rotate the other way.
+    pub fn rotate(&mut self, by: i64) {
+        self.offset = self.view().wrap(by);
+    }
+
+    /// A view rotated by `by` without changing the ring.
+    pub fn rotated(&self, by: i64) -> RingView<'_, T> {
+        self.view().rotated(by)
+    }
+
+    pub fn view(&self) -> RingView<'_, T> {
+        RingView {
+            items: &self.items,
+            offset: self.offset,
+        }
+    }
+
+    /// Item at `index`, wrapping round either end. `None` for an empty ring.
+    pub fn get(&self, index: i64) -> Option<&T> {
+        self.view().get(index)
+    }
+
+    /// Item reached from `start` after `steps` moves of `stride`.
+    pub fn after(&self, start: i64, stride: i64, steps: i64) -> &T {
+        self.view().after(start, stride, steps)
+    }
+
+    /// Every item once, starting from index 0.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.view().iter()
+    }
+
+    /// Every run of `size` neighbouring items, one starting at each index, wrapping
+    /// past the end.
+    pub fn windows(&self, size: usize) -> impl Iterator<Item = Vec<&T>> {
+        self.view().windows(size)
+    }
+}
+
+impl<T> Default for Ring<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> RingView<'a, T> {
+    fn wrap(&self, index: i64) -> usize {
+        let len = self.items.len() as i64;
+        ((self.offset as i64 + index.rem_euclid(len.max(1))) % len.max(1)) as usize
+    }
+
+    fn at(&self, index: i64) -> &'a T {
+        self.get(index).expect("Indexed an empty ring")
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn rotated(self, by: i64) -> Self {
+        Self {
+            items: self.items,
+            offset: self.wrap(by),
+        }
+    }
+
+    pub fn get(&self, index: i64) -> Option<&'a T> {
+        self.items.get(self.wrap(index))
+    }
+
+    pub fn after(&self, start: i64, stride: i64, steps: i64) -> &'a T {
+        let len = self.items.len().max(1) as i128;
+        let moved = (stride as i128 * steps as i128).rem_euclid(len) as i64;
+        self.at(start.rem_euclid(len as i64) + moved)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> + use<'a, T> {
+        let view = *self;
+        (0..self.items.len() as i64).map(move |idx| view.at(idx))
+    }
+
+    pub fn windows(&self, size: usize) -> impl Iterator<Item = Vec<&'a T>> + use<'a, T> {
+        let view = *self;
+        (0..self.items.len() as i64).map(move |start| {
+            (start..start + size as i64)
+                .map(|idx| view.at(idx))
+                .collect()
+        })
+    }
+}
+
+impl<T> Index<i64> for Ring<T> {
+    type Output = T;
+
+    /// Panics on an empty ring.
+    fn index(&self, index: i64) -> &Self::Output {
+        self.view().at(index)
+    }
+}
+
+impl<T> Index<i64> for RingView<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: i64) -> &Self::Output {
+        self.at(index)
+    }
+}
+
+/// Where something ends up after `steps` moves back and forth along `0..=len`, turning
+/// round at each end.
+pub fn bounce(steps: usize, len: usize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    let pos = steps % (2 * len);
+    pos.min(2 * len - pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indexing() {
+        let ring = Ring::from(vec!['a', 'b', 'c', 'd']);
+        assert_eq!('a', ring[0]);
+        assert_eq!('d', ring[-1]);
+        assert_eq!('b', ring[9]);
+        assert_eq!('c', *ring.after(1, 3, 3));
+        assert_eq!('a', *ring.after(0, -5, 4));
+        assert_eq!(None, Ring::<char>::new().get(3));
+    }
+
+    #[test]
+    fn test_rotation() {
+        let mut ring = (1..=5).collect::<Ring<_>>();
+        let view = ring.rotated(-2);
+        assert_eq!(
+            vec![4, 5, 1, 2, 3],
+            view.iter().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(2, view.rotated(3)[0]);
+        ring.rotate(7);
+        assert_eq!(3, ring[0]);
+        assert_eq!(2, ring.offset());
+        ring.push(9);
+        assert_eq!(
+            vec![3, 4, 5, 1, 2, 9],
+            ring.iter().copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_windows() {
+        let ring = Ring::from(vec![1, 2, 3]);
+        let windows = ring
+            .windows(2)
+            .map(|w| w.into_iter().copied().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![vec![1, 2], vec![2, 3], vec![3, 1]], windows);
+    }
+
+    #[test]
+    fn test_bounce() {
+        let positions = (0..9).map(|steps| bounce(steps, 3)).collect::<Vec<_>>();
+        assert_eq!(vec![0, 1, 2, 3, 2, 1, 0, 1, 2], positions);
+    }
+}
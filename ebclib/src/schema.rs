@@ -0,0 +1,686 @@
+//! Input layouts described in TOML, checked and loaded before a quest runs.
+//!
+//! A spec lists runs of non-blank lines in order, each with a format. A run without a
+//! `count` takes every line left.
+//!
+//! ```toml
+//! [[lines]]
+//! count = 1
+//! format = "list"
+//! separator = ","
+//! value = "u32"
+//!
+//! [[lines]]
+//! format = "chars"
+//! rectangular = true
+//! ```
+//!
+//! Formats are `value`, `list` (`separator`, default `,`), `words`, `tuple` (`values`),
+//! `keyed` (`KEY:a,b,c`), `chars` (`allowed`, `rectangular`), `blocks` (`width`,
+//! `gap`) and `text`. Values are `i64`, `u64`, `u32`, `usize`, `u8`, `char` or `word`.
+//!
+//! [`Schema::load_as`] turns a checked input into any [`FromValue`] type. A run with
+//! `count = 1` gives its one line, any other run a list of lines, and the input a tuple
+//! of runs, or just the run if there's only one.
+
+use std::{fmt::Display, fs, path::Path, str::FromStr};
+
+use toml_edit::{DocumentMut, Item, Table};
+
+use crate::{
+    parse::{ParseError, Source, Span},
+    registry::QuestId,
+};
+
+/// What a single value must parse as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    I64,
+    U64,
+    U32,
+    Usize,
+    U8,
+    Char,
+    Word,
+}
+
+impl FromStr for Kind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "i64" => Kind::I64,
+            "u64" => Kind::U64,
+            "u32" => Kind::U32,
+            "usize" => Kind::Usize,
+            "u8" => Kind::U8,
+            "char" => Kind::Char,
+            "word" => Kind::Word,
+            _ => return Err(format!("Unknown value type {s}")),
+        })
+    }
+}
+
+impl Kind {
+    fn check(&self, span: Span) -> Result<Value, ParseError> {
+        match self {
+            Kind::I64 => span.parse::<i64>().map(|n| Value::Int(n.into())),
+            Kind::U64 => span.parse::<u64>().map(|n| Value::Int(n.into())),
+            Kind::U32 => span.parse::<u32>().map(|n| Value::Int(n.into())),
+            Kind::Usize => span.parse::<usize>().map(|n| Value::Int(n as i128)),
+            Kind::U8 => span.parse::<u8>().map(|n| Value::Int(n.into())),
+            Kind::Char => span.parse::<char>().map(Value::Char),
+            Kind::Word if span.is_empty() || span.as_str().contains(char::is_whitespace) => {
+                Err(span.error("a word"))
+            }
+            Kind::Word => Ok(Value::Text(span.as_str().to_string())),
+        }
+    }
+}
+
+/// How each line of a run is laid out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Format {
+    Value(Kind),
+    List {
+        separator: char,
+        value: Kind,
+    },
+    Words(Kind),
+    Tuple(Vec<Kind>),
+    Keyed(Kind),
+    Chars {
+        allowed: Option<String>,
+        rectangular: bool,
+    },
+    Blocks {
+        width: usize,
+        gap: usize,
+    },
+    Text,
+}
+
+/// A run of non-blank lines sharing a format, `None` for every line left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lines {
+    pub count: Option<usize>,
+    pub format: Format,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schema {
+    pub lines: Vec<Lines>,
+}
+
+/// An input as checked against a schema, each value typed by its [`Kind`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    /// Any of the integer kinds.
+    Int(i128),
+    Char(char),
+    Text(String),
+    List(Vec<Value>),
+    Keyed(String, Vec<Value>),
+}
+
+impl Value {
+    /// The text of a word or line, or the key of a keyed line.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Text(text) | Value::Keyed(text, _) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn get<T: FromValue>(&self) -> Result<T, String> {
+        T::from_value(self)
+    }
+
+    /// The items of a list or keyed line, empty for anything else.
+    pub fn items(&self) -> &[Value] {
+        match self {
+            Value::List(items) | Value::Keyed(_, items) => items,
+            _ => &[],
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Value::Int(n) => format!("{n}"),
+            Value::Char(ch) => format!("{ch:?}"),
+            Value::Text(text) => format!("{text:?}"),
+            Value::List(items) => format!("a list of {}", items.len()),
+            Value::Keyed(key, _) => format!("keyed line {key:?}"),
+        }
+    }
+}
+
+/// Types a loaded [`Value`] can become. Errors say what was expected, for a report.
+pub trait FromValue: Sized {
+    fn from_value(value: &Value) -> Result<Self, String>;
+}
+
+macro_rules! int_from_value {
+    ($($int:ty),*) => {$(
+        impl FromValue for $int {
+            fn from_value(value: &Value) -> Result<Self, String> {
+                match value {
+                    Value::Int(n) => <$int>::try_from(*n)
+                        .map_err(|_| format!("{} but {n} is out of range", stringify!($int))),
+                    other => Err(format!("{} but found {}", stringify!($int), other.describe())),
+                }
+            }
+        }
+    )*};
+}
+
+int_from_value!(i32, i64, u8, u16, u32, u64, usize);
+
+impl FromValue for char {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Char(ch) => Ok(*ch),
+            other => Err(format!("a char but found {}", other.describe())),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Text(text) => Ok(text.clone()),
+            Value::Char(ch) => Ok(ch.to_string()),
+            other => Err(format!("text but found {}", other.describe())),
+        }
+    }
+}
+
+/// From the items of a list or keyed line.
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::List(items) | Value::Keyed(_, items) => {
+                items.iter().map(T::from_value).collect()
+            }
+            other => Err(format!("a list but found {}", other.describe())),
+        }
+    }
+}
+
+/// From a list of exactly as many values, or a keyed line as its key then its items.
+macro_rules! tuple_from_value {
+    ($len:literal; $($t:ident $idx:tt),+) => {
+        impl<$($t: FromValue),+> FromValue for ($($t,)+) {
+            fn from_value(value: &Value) -> Result<Self, String> {
+                let keyed;
+                let items = match value {
+                    Value::List(items) => items.as_slice(),
+                    Value::Keyed(key, items) => {
+                        keyed = [Value::Text(key.clone()), Value::List(items.clone())];
+                        keyed.as_slice()
+                    }
+                    other => return Err(format!("a list but found {}", other.describe())),
+                };
+                if items.len() != $len {
+                    return Err(format!(concat!($len, " values but found {}"), items.len()));
+                }
+                Ok(($($t::from_value(&items[$idx])?,)+))
+            }
+        }
+    };
+}
+
+tuple_from_value!(2; A 0, B 1);
+tuple_from_value!(3; A 0, B 1, C 2);
+tuple_from_value!(4; A 0, B 1, C 2, D 3);
+
+/// Every mismatch found in an input.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Report {
+    pub errors: Vec<ParseError>,
+}
+
+/// The same as `Display`, so a `main` returning a report shows it readably.
+impl std::fmt::Debug for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, error) in self.errors.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "{error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<ParseError> for Report {
+    fn from(value: ParseError) -> Self {
+        Self {
+            errors: vec![value],
+        }
+    }
+}
+
+impl FromStr for Schema {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let doc = s.parse::<DocumentMut>().map_err(|e| e.to_string())?;
+        let Some(runs) = doc.get("lines").and_then(Item::as_array_of_tables) else {
+            return Err("Missing [[lines]]".to_string());
+        };
+        let lines = runs
+            .iter()
+            .enumerate()
+            .map(|(idx, run)| parse_lines(run).map_err(|e| format!("{e} in run {}", idx + 1)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(idx) = lines[..lines.len().saturating_sub(1)]
+            .iter()
+            .position(|run| run.count.is_none())
+        {
+            return Err(format!(
+                "Only the last run can leave out count, not run {}",
+                idx + 1
+            ));
+        }
+        Ok(Self { lines })
+    }
+}
+
+fn parse_lines(run: &Table) -> Result<Lines, String> {
+    let int = |key: &str| -> Result<Option<usize>, String> {
+        match run.get(key) {
+            None => Ok(None),
+            Some(item) => item
+                .as_integer()
+                .and_then(|n| usize::try_from(n).ok())
+                .map(Some)
+                .ok_or_else(|| format!("{key} must be a whole number")),
+        }
+    };
+    let text = |key: &str| -> Result<Option<&str>, String> {
+        match run.get(key) {
+            None => Ok(None),
+            Some(item) => item
+                .as_str()
+                .map(Some)
+                .ok_or_else(|| format!("{key} must be a string")),
+        }
+    };
+    let value = || -> Result<Kind, String> { text("value")?.unwrap_or("word").parse() };
+    let format = match text("format")?.ok_or("Missing format")? {
+        "value" => Format::Value(value()?),
+        "list" => {
+            let separator = text("separator")?.unwrap_or(",");
+            let mut chars = separator.chars();
+            let (Some(separator), None) = (chars.next(), chars.next()) else {
+                return Err("separator must be one character".to_string());
+            };
+            Format::List {
+                separator,
+                value: value()?,
+            }
+        }
+        "words" => Format::Words(value()?),
+        "tuple" => {
+            let values = run
+                .get("values")
+                .and_then(Item::as_array)
+                .ok_or("tuple needs values")?;
+            Format::Tuple(
+                values
+                    .iter()
+                    .map(|v| v.as_str().ok_or("values must be strings")?.parse())
+                    .collect::<Result<_, String>>()?,
+            )
+        }
+        "keyed" => Format::Keyed(value()?),
+        "chars" => Format::Chars {
+            allowed: text("allowed")?.map(str::to_string),
+            rectangular: run
+                .get("rectangular")
+                .map(|item| item.as_bool().ok_or("rectangular must be true or false"))
+                .transpose()?
+                .unwrap_or(false),
+        },
+        "blocks" => Format::Blocks {
+            width: int("width")?
+                .filter(|width| *width > 0)
+                .ok_or("blocks needs a width of at least 1")?,
+            gap: int("gap")?.unwrap_or(0),
+        },
+        "text" => Format::Text,
+        other => return Err(format!("Unknown format {other}")),
+    };
+    Ok(Lines {
+        count: int("count")?,
+        format,
+    })
+}
+
+impl Schema {
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| text.parse())
+            .map_err(|e| format!("Spec {}: {e}", path.display()))
+    }
+
+    /// Check every line, giving one value per line for each run.
+    pub fn load(&self, source: &Source) -> Result<Vec<Vec<Value>>, Report> {
+        let mut lines = source.lines().peekable();
+        let mut loaded = vec![];
+        let mut errors = vec![];
+        for (idx, run) in self.lines.iter().enumerate() {
+            let spans = match run.count {
+                Some(count) => lines.by_ref().take(count).collect::<Vec<_>>(),
+                None => lines.by_ref().collect(),
+            };
+            if spans.len() < run.count.unwrap_or(1) {
+                errors.push(source.error(format!("more lines for run {}", idx + 1)));
+            }
+            let mut values = vec![];
+            for span in spans.iter() {
+                match run.format.check(*span) {
+                    Ok(value) => values.push(value),
+                    Err(e) => errors.push(e),
+                }
+            }
+            if let Format::Chars {
+                rectangular: true, ..
+            } = run.format
+                && let Some(span) = spans
+                    .iter()
+                    .find(|s| s.as_str().chars().count() != spans[0].as_str().chars().count())
+            {
+                errors.push(span.error(format!(
+                    "the same length as line {}",
+                    spans[0].line_number()
+                )));
+            }
+            loaded.push(values);
+        }
+        if let Some(extra) = lines.next() {
+            errors.push(extra.error("no more lines"));
+        }
+        if errors.is_empty() {
+            Ok(loaded)
+        } else {
+            Err(Report { errors })
+        }
+    }
+
+    /// Check and load an input as `T`, shaped as the module docs describe.
+    pub fn load_as<T: FromValue>(&self, source: &Source) -> Result<T, Report> {
+        let mut runs = self
+            .load(source)?
+            .into_iter()
+            .zip(self.lines.iter())
+            .map(|(mut values, run)| match run.count {
+                Some(1) => values.pop().unwrap(),
+                _ => Value::List(values),
+            })
+            .collect::<Vec<_>>();
+        let value = match runs.len() {
+            1 => runs.pop().unwrap(),
+            _ => Value::List(runs),
+        };
+        T::from_value(&value).map_err(|e| Report::from(source.error(e)))
+    }
+}
+
+impl Format {
+    fn check(&self, span: Span) -> Result<Value, ParseError> {
+        let list = |items: Vec<Span>, kind: &Kind| {
+            items
+                .into_iter()
+                .map(|item| kind.check(item))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::List)
+        };
+        match self {
+            Format::Value(kind) => kind.check(span.trim()),
+            Format::List { separator, value } => {
+                list(span.split(*separator).map(|s| s.trim()).collect(), value)
+            }
+            Format::Words(kind) => list(span.words().collect(), kind),
+            Format::Tuple(kinds) => {
+                let words = span.words().collect::<Vec<_>>();
+                if words.len() != kinds.len() {
+                    return Err(span.error(format!("{} fields", kinds.len())));
+                }
+                let fields = words
+                    .iter()
+                    .zip(kinds)
+                    .map(|(word, kind)| kind.check(*word));
+                fields.collect::<Result<_, _>>().map(Value::List)
+            }
+            Format::Keyed(kind) => {
+                let (key, values) = span.keyed()?;
+                let Value::List(values) = list(values, kind)? else {
+                    unreachable!()
+                };
+                Ok(Value::Keyed(key.as_str().to_string(), values))
+            }
+            Format::Chars { allowed, .. } => {
+                let mut chars = vec![];
+                for (idx, ch) in span.as_str().char_indices() {
+                    if let Some(allowed) = allowed
+                        && !allowed.contains(ch)
+                    {
+                        let (_, rest) = span.split_at(idx);
+                        let (bad, _) = rest.split_at(ch.len_utf8());
+                        return Err(bad.error(format!("one of {allowed:?}")));
+                    }
+                    chars.push(Value::Char(ch));
+                }
+                Ok(Value::List(chars))
+            }
+            Format::Blocks { width, gap } => Ok(Value::List(
+                span.blocks(*width, *gap)
+                    .map(|block| Value::Text(block.map(|b| b.as_str()).unwrap_or("").to_string()))
+                    .collect(),
+            )),
+            Format::Text => Ok(Value::Text(span.as_str().to_string())),
+        }
+    }
+}
+
+/// Spec for one part's input: `questNN.P.toml` if there is one, else `questNN.toml`.
+pub fn spec_path(dir: &Path, number: u8, part: u8) -> Option<std::path::PathBuf> {
+    [
+        format!("quest{number:02}.{part}.toml"),
+        format!("quest{number:02}.toml"),
+    ]
+    .into_iter()
+    .map(|name| dir.join(name))
+    .find(|path| path.exists())
+}
+
+/// Check and load an input file like `koa2024/inputs/quest08.1.txt` against the spec
+/// beside it.
+pub fn load_input<T: FromValue>(path: &str) -> Result<T, Report> {
+    let input = Path::new(path);
+    let spec = input
+        .file_stem()
+        .and_then(|stem| stem.to_str()?.strip_prefix("quest")?.split_once('.'))
+        .and_then(|(number, part)| Some((number.parse().ok()?, part.parse().ok()?)))
+        .and_then(|(number, part)| spec_path(input.parent()?, number, part))
+        .ok_or_else(|| ParseError::new("a spec for the input").in_file(path))?;
+    let schema = Schema::read(&spec).map_err(|e| Report::from(ParseError::new(e)))?;
+    schema.load_as(&Source::read(path)?)
+}
+
+/// Check every input of a quest that has both a file and a spec, giving the number
+/// checked, or `None` if the quest has no spec at all. A part that can't be checked
+/// doesn't stop the rest, so the report lists every problem at once.
+pub fn check_quest(quest: &QuestId) -> Result<Option<usize>, Report> {
+    let dir = Path::new(&format!("{}{}", quest.event, quest.year)).join("inputs");
+    check_inputs(&dir, quest.number)
+}
+
+fn check_inputs(dir: &Path, number: u8) -> Result<Option<usize>, Report> {
+    let mut checked = None;
+    let mut errors = vec![];
+    for part in 1..=3 {
+        let input = dir.join(format!("quest{number:02}.{part}.txt"));
+        let Some(spec) = spec_path(dir, number, part) else {
+            continue;
+        };
+        let count = checked.get_or_insert(0);
+        if !input.exists() {
+            continue;
+        }
+        let schema = match Schema::read(&spec) {
+            Ok(schema) => schema,
+            Err(e) => {
+                errors.push(ParseError::new(e));
+                continue;
+            }
+        };
+        let source = match Source::read(&input.to_string_lossy()) {
+            Ok(source) => source,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+        match schema.load(&source) {
+            Ok(_) => *count += 1,
+            Err(report) => errors.extend(report.errors),
+        }
+    }
+    if errors.is_empty() {
+        Ok(checked)
+    } else {
+        Err(Report { errors })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHEELS: &str = r#"
+[[lines]]
+count = 1
+format = "list"
+value = "u32"
+
+[[lines]]
+format = "blocks"
+width = 3
+gap = 1
+"#;
+
+    #[test]
+    fn test_spec() {
+        let schema = WHEELS.parse::<Schema>().unwrap();
+        assert_eq!(
+            Format::List {
+                separator: ',',
+                value: Kind::U32
+            },
+            schema.lines[0].format
+        );
+        assert_eq!(None, schema.lines[1].count);
+        let err = "[[lines]]\nformat = \"list\"\nvalue = \"f32\""
+            .parse::<Schema>()
+            .unwrap_err();
+        assert_eq!("Unknown value type f32 in run 1", err);
+        assert!(
+            "[[lines]]\nformat = \"text\"\n[[lines]]\nformat = \"text\""
+                .parse::<Schema>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_load() {
+        let schema = WHEELS.parse::<Schema>().unwrap();
+        let loaded = schema
+            .load(&Source::new("1,2,3\n\n^_^ -.-\n    >.<"))
+            .unwrap();
+        assert_eq!(Ok(3), loaded[0][0].items()[2].get::<u32>());
+        assert_eq!(2, loaded[1].len());
+        assert_eq!(Some(""), loaded[1][1].items()[0].as_str());
+
+        let report = schema.load(&Source::new("1,x,-3")).unwrap_err();
+        let columns = report
+            .errors
+            .iter()
+            .map(|e| e.location.as_ref().map(|at| at.column))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![Some(3), None], columns);
+    }
+
+    #[test]
+    fn test_grid() {
+        let schema = "[[lines]]\ncount = 1\nformat = \"chars\"\nallowed = \"LR\"\n\
+            [[lines]]\nformat = \"chars\"\nrectangular = true"
+            .parse::<Schema>()
+            .unwrap();
+        assert!(schema.load(&Source::new("LRL\n\n>-A\n--<")).is_ok());
+        let report = schema.load(&Source::new("LXR\n\n>-A\n--")).unwrap_err();
+        assert_eq!(2, report.errors.len());
+        assert_eq!("one of \"LR\"", report.errors[0].expected);
+        assert_eq!(4, report.errors[1].location.as_ref().unwrap().line);
+        assert!(schema.load(&Source::new("L\n\né-\n-<")).is_ok());
+    }
+
+    #[test]
+    fn test_load_as() {
+        let schema = WHEELS.parse::<Schema>().unwrap();
+        let (wheels, faces): (Vec<u8>, Vec<Vec<String>>) =
+            schema.load_as(&Source::new("1,2,3\n\n^_^ -.-")).unwrap();
+        assert_eq!(vec![1, 2, 3], wheels);
+        assert_eq!(vec![vec!["^_^".to_string(), "-.-".to_string()]], faces);
+        let report = schema
+            .load_as::<(Vec<u8>, Vec<Vec<char>>)>(&Source::new("1,300\n\n^_^"))
+            .unwrap_err();
+        assert_eq!("u8 but 300 is out of range", report.errors[0].expected);
+
+        let keyed = "[[lines]]\nformat = \"keyed\"\nvalue = \"i64\""
+            .parse::<Schema>()
+            .unwrap();
+        let notes: Vec<(String, Vec<i64>)> = keyed.load_as(&Source::new("A:1,-2\nB:3")).unwrap();
+        assert_eq!(("A".to_string(), vec![1, -2]), notes[0]);
+        assert!(
+            "[[lines]]\nformat = \"blocks\"\nwidth = 0"
+                .parse::<Schema>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_check_inputs() {
+        let dir = std::env::temp_dir().join(format!("ebclib-check-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let spec = "[[lines]]\nformat = \"list\"\nvalue = \"u8\"";
+        fs::write(dir.join("quest01.1.toml"), spec).unwrap();
+        fs::write(dir.join("quest01.1.txt"), "1,300").unwrap();
+        fs::write(dir.join("quest01.2.toml"), "[[lines]]\nformat = \"nope\"").unwrap();
+        fs::write(dir.join("quest01.2.txt"), "1,2").unwrap();
+        fs::write(dir.join("quest01.3.toml"), spec).unwrap();
+        fs::write(dir.join("quest01.3.txt"), "1,x").unwrap();
+        let report = check_inputs(&dir, 1).unwrap_err();
+        fs::write(dir.join("quest01.1.txt"), "1,3").unwrap();
+        fs::write(dir.join("quest01.3.txt"), "4").unwrap();
+        fs::remove_file(dir.join("quest01.2.toml")).unwrap();
+        fs::remove_file(dir.join("quest01.2.txt")).unwrap();
+        let checked = check_inputs(&dir, 1);
+        fs::remove_dir_all(&dir).unwrap();
+        // The bad spec for part 2 doesn't hide the bad input for part 3.
+        assert_eq!(3, report.errors.len());
+        assert!(report.errors[1].expected.starts_with("Spec "));
+        assert_eq!(Some("u8"), report.errors[2].expected.split(' ').next());
+        assert_eq!(Some(2), checked.unwrap());
+    }
+}
@@ -0,0 +1,610 @@
+//! Graph searches over nodes supplied by a neighbour function.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::{BuildHasher, Hash, RandomState},
+    ops::Add,
+    rc::Rc,
+};
+
+use crate::{grid::Grid, par};
+
+/// Somewhere to record how many steps it took to reach each node.
+pub trait DistanceMap<N> {
+    fn distance(&self, node: &N) -> Option<usize>;
+
+    fn set_distance(&mut self, node: &N, distance: usize);
+
+    /// Forget every distance so the map can be used for another search.
+    fn reset(&mut self);
+}
+
+impl<N: Hash + Eq + Clone> DistanceMap<N> for HashMap<N, usize> {
+    fn distance(&self, node: &N) -> Option<usize> {
+        self.get(node).copied()
+    }
+
+    fn set_distance(&mut self, node: &N, distance: usize) {
+        self.insert(node.clone(), distance);
+    }
+
+    fn reset(&mut self) {
+        self.clear();
+    }
+}
+
+impl DistanceMap<(usize, usize)> for Grid<Option<usize>> {
+    fn distance(&self, node: &(usize, usize)) -> Option<usize> {
+        self.get(*node).copied().flatten()
+    }
+
+    fn set_distance(&mut self, node: &(usize, usize), distance: usize) {
+        self[*node] = Some(distance);
+    }
+
+    fn reset(&mut self) {
+        self.fill(None);
+    }
+}
+
+/// Breadth first search from every start at once, writing the fewest steps to each
+/// reachable node into `distances`. `queue` is only scratch space and is passed in so
+/// repeated searches can reuse its allocation.
+pub fn bfs_into<N, M, I>(
+    distances: &mut M,
+    queue: &mut VecDeque<N>,
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+) where
+    M: DistanceMap<N>,
+    I: IntoIterator<Item = N>,
+{
+    queue.clear();
+    for start in starts {
+        if distances.distance(&start).is_none() {
+            distances.set_distance(&start, 0);
+            queue.push_back(start);
+        }
+    }
+    while let Some(node) = queue.pop_front() {
+        let next = distances.distance(&node).unwrap() + 1;
+        for neighbour in neighbours(&node) {
+            if distances.distance(&neighbour).is_none() {
+                distances.set_distance(&neighbour, next);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+}
+
+/// Fewest steps from the nearest start to every reachable node.
+pub fn bfs_distances<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+) -> HashMap<N, usize>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut distances = HashMap::new();
+    bfs_into(&mut distances, &mut VecDeque::new(), starts, neighbours);
+    distances
+}
+
+/// [`bfs_distances`] over the four way moves between cells where `open` is true.
+pub fn grid_distances<T>(
+    grid: &Grid<T>,
+    starts: impl IntoIterator<Item = (usize, usize)>,
+    open: impl Fn(&T) -> bool,
+) -> Grid<Option<usize>> {
+    let mut distances = Grid::new(grid.rows(), grid.cols(), None);
+    bfs_into(&mut distances, &mut VecDeque::new(), starts, |node| {
+        grid.cardinals(*node).filter(|n| open(&grid[*n]))
+    });
+    distances
+}
+
+/// Sum of the distances from each source separately, for the nodes every source
+/// can reach. Runs one search per source, reusing the same buffers each time.
+pub fn sum_distances<N, I>(sources: &[N], mut neighbours: impl FnMut(&N) -> I) -> HashMap<N, usize>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut totals: HashMap<N, (usize, usize)> = HashMap::new();
+    let mut distances = HashMap::new();
+    let mut queue = VecDeque::new();
+    for source in sources {
+        distances.reset();
+        bfs_into(
+            &mut distances,
+            &mut queue,
+            [source.clone()],
+            &mut neighbours,
+        );
+        for (node, distance) in distances.drain() {
+            let total = totals.entry(node).or_default();
+            total.0 += distance;
+            total.1 += 1;
+        }
+    }
+    totals
+        .into_iter()
+        .filter(|(_, (_, reached))| *reached == sources.len())
+        .map(|(node, (total, _))| (node, total))
+        .collect()
+}
+
+/// [`sum_distances`] on a grid. Each of the [`par::workers`] searches from its own run
+/// of the sources, reusing one dense buffer, and their totals are added up after.
+pub fn sum_grid_distances<T: Sync>(
+    grid: &Grid<T>,
+    sources: &[(usize, usize)],
+    open: impl Fn(&T) -> bool + Sync,
+) -> Grid<Option<usize>> {
+    let size = sources.len().div_ceil(par::workers()).max(1);
+    let runs = sources.chunks(size).collect::<Vec<_>>();
+    let partials = par::par_map(&runs, |run| {
+        let mut totals = Grid::new(grid.rows(), grid.cols(), Some(0));
+        let mut distances = Grid::new(grid.rows(), grid.cols(), None);
+        let mut queue = VecDeque::new();
+        for source in run.iter() {
+            distances.reset();
+            bfs_into(&mut distances, &mut queue, [*source], |node| {
+                grid.cardinals(*node).filter(|n| open(&grid[*n]))
+            });
+            add_totals(&mut totals, &distances);
+        }
+        totals
+    });
+    let mut totals = Grid::new(grid.rows(), grid.cols(), Some(0));
+    for partial in partials.iter() {
+        add_totals(&mut totals, partial);
+    }
+    totals
+}
+
+/// Add `distances` into `totals`, leaving `None` wherever either is unreached.
+fn add_totals(totals: &mut Grid<Option<usize>>, distances: &Grid<Option<usize>>) {
+    for (pos, distance) in distances.iter() {
+        let total = &mut totals[pos];
+        *total = total.zip(*distance).map(|(t, d)| t + d);
+    }
+}
+
+/// Costs that can be summed and ordered. `Default` is the cost of doing nothing.
+pub trait Cost: Copy + Ord + Add<Output = Self> + Default {}
+
+impl<T: Copy + Ord + Add<Output = T> + Default> Cost for T {}
+
+/// How a [`Search`] avoids expanding the same state twice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visited {
+    /// Never queue a state that has been queued before. Right when every move costs
+    /// the same, so the default for [`Search::bfs`].
+    FirstSeen,
+    /// Queue a state again only when it is reached more cheaply. The default for
+    /// [`Search::dijkstra`] and [`Search::astar`].
+    BestCost,
+    /// Don't track states at all, for spaces that are trees.
+    Off,
+}
+
+/// Counters from the last run of a [`Search`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// States taken off the frontier and expanded.
+    pub expanded: usize,
+    /// States added to the frontier.
+    pub generated: usize,
+    /// States dropped by the prune hook or because another state dominated them.
+    pub pruned: usize,
+    /// Largest the frontier got.
+    pub peak_frontier: usize,
+}
+
+/// A goal that was reached, the cost of getting there and the states along the way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Found<S, C> {
+    pub cost: C,
+    /// From the start to the goal, both included.
+    pub path: Vec<S>,
+}
+
+impl<S, C> Found<S, C> {
+    pub fn goal(&self) -> &S {
+        self.path.last().unwrap()
+    }
+}
+
+type Successors<'a, S, C> = Box<dyn FnMut(&S, &mut Vec<(S, C)>) + 'a>;
+type Prune<'a, S, C> = Box<dyn FnMut(&S, C) -> bool + 'a>;
+type Dominates<'a, S> = Box<dyn Fn(&S, &S) -> bool + 'a>;
+
+/// Best first search over states supplied by a successor function. The hooks default to
+/// having no goal, no heuristic and no pruning; set the ones needed then run one of
+/// [`Search::bfs`], [`Search::dijkstra`] or [`Search::astar`].
+pub struct Search<'a, S, C> {
+    successors: Successors<'a, S, C>,
+    goal: Box<dyn FnMut(&S) -> bool + 'a>,
+    heuristic: Box<dyn FnMut(&S) -> C + 'a>,
+    prune: Prune<'a, S, C>,
+    prune_on_pop: bool,
+    dominance: Option<Dominance<'a, S>>,
+    visited: Option<Visited>,
+    stats: Stats,
+}
+
+struct Dominance<'a, S> {
+    bucket: Box<dyn Fn(&S) -> u64 + 'a>,
+    dominates: Dominates<'a, S>,
+}
+
+/// A state on the frontier and how it was reached.
+struct Node<S, C> {
+    state: S,
+    cost: C,
+    parent: Option<usize>,
+}
+
+enum Frontier<C> {
+    Fifo(VecDeque<usize>),
+    Heap(BinaryHeap<Reverse<(C, usize)>>),
+}
+
+impl<'a, S: Clone + Hash + Eq, C: Cost> Search<'a, S, C> {
+    /// `successors` gives each state reachable in one move and the cost of that move.
+    pub fn new<I>(mut successors: impl FnMut(&S) -> I + 'a) -> Self
+    where
+        I: IntoIterator<Item = (S, C)>,
+    {
+        Self {
+            successors: Box::new(move |state, out| out.extend(successors(state))),
+            goal: Box::new(|_| false),
+            heuristic: Box::new(|_| C::default()),
+            prune: Box::new(|_, _| false),
+            prune_on_pop: false,
+            dominance: None,
+            visited: None,
+            stats: Stats::default(),
+        }
+    }
+
+    /// Stop at the first state taken off the frontier that matches `goal`.
+    pub fn goal(mut self, goal: impl FnMut(&S) -> bool + 'a) -> Self {
+        self.goal = Box::new(goal);
+        self
+    }
+
+    /// Lower bound on the remaining cost, used by [`Search::astar`]. It has to never
+    /// overestimate for the answer to be the cheapest.
+    pub fn heuristic(mut self, heuristic: impl FnMut(&S) -> C + 'a) -> Self {
+        self.heuristic = Box::new(heuristic);
+        self
+    }
+
+    /// Drop states for which `prune` returns true, given the state and its cost so far.
+    /// It is called for every new state so it can keep its own bookkeeping.
+    pub fn prune(mut self, prune: impl FnMut(&S, C) -> bool + 'a) -> Self {
+        self.prune = Box::new(prune);
+        self
+    }
+
+    /// Call the prune hook when a state is taken off the frontier instead, just after
+    /// the goal check, so its bookkeeping only sees states in the order they are expanded.
+    pub fn prune_on_pop(mut self) -> Self {
+        self.prune_on_pop = true;
+        self
+    }
+
+    /// Drop a state when one already reached at no more cost has the same `key` and
+    /// `dominates` it, i.e. can do anything the new state can.
+    pub fn dominance<K: Hash + Eq>(
+        mut self,
+        key: impl Fn(&S) -> K + 'a,
+        dominates: impl Fn(&S, &S) -> bool + 'a,
+    ) -> Self {
+        let key = Rc::new(key);
+        let hasher = RandomState::new();
+        let bucket_key = key.clone();
+        self.dominance = Some(Dominance {
+            bucket: Box::new(move |state| hasher.hash_one(bucket_key(state))),
+            dominates: Box::new(move |a, b| key(a) == key(b) && dominates(a, b)),
+        });
+        self
+    }
+
+    pub fn visited(mut self, visited: Visited) -> Self {
+        self.visited = Some(visited);
+        self
+    }
+
+    /// Counters from the last run.
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Expand states in the order they were found. Costs are still summed, but the
+    /// answer is only the cheapest when every move costs the same.
+    pub fn bfs(&mut self, starts: impl IntoIterator<Item = S>) -> Option<Found<S, C>> {
+        let visited = self.visited.unwrap_or(Visited::FirstSeen);
+        self.run(starts, Frontier::Fifo(VecDeque::new()), visited, false)
+    }
+
+    /// Expand the cheapest state first.
+    pub fn dijkstra(&mut self, starts: impl IntoIterator<Item = S>) -> Option<Found<S, C>> {
+        let visited = self.visited.unwrap_or(Visited::BestCost);
+        self.run(starts, Frontier::Heap(BinaryHeap::new()), visited, false)
+    }
+
+    /// Expand the state with the lowest cost plus heuristic first.
+    pub fn astar(&mut self, starts: impl IntoIterator<Item = S>) -> Option<Found<S, C>> {
+        let visited = self.visited.unwrap_or(Visited::BestCost);
+        self.run(starts, Frontier::Heap(BinaryHeap::new()), visited, true)
+    }
+
+    fn run(
+        &mut self,
+        starts: impl IntoIterator<Item = S>,
+        frontier: Frontier<C>,
+        visited: Visited,
+        informed: bool,
+    ) -> Option<Found<S, C>> {
+        self.stats = Stats::default();
+        let mut run = Run {
+            nodes: vec![],
+            best: HashMap::new(),
+            buckets: HashMap::new(),
+            frontier,
+            visited,
+            informed,
+        };
+        for state in starts {
+            let node = Node {
+                state,
+                cost: C::default(),
+                parent: None,
+            };
+            run.offer(self, node);
+        }
+        let mut next = vec![];
+        while let Some(idx) = run.frontier.pop() {
+            let Node { state, cost, .. } = &run.nodes[idx];
+            let (state, cost) = (state.clone(), *cost);
+            if visited == Visited::BestCost && run.best[&state] < cost {
+                // A cheaper way here was found after this one was queued.
+                continue;
+            }
+            if (self.goal)(&state) {
+                return Some(Found {
+                    cost,
+                    path: run.path(idx),
+                });
+            }
+            if self.prune_on_pop && (self.prune)(&state, cost) {
+                self.stats.pruned += 1;
+                continue;
+            }
+            self.stats.expanded += 1;
+            (self.successors)(&state, &mut next);
+            for (state, step) in next.drain(..) {
+                let node = Node {
+                    state,
+                    cost: cost + step,
+                    parent: Some(idx),
+                };
+                run.offer(self, node);
+            }
+        }
+        None
+    }
+}
+
+/// Everything one run of a [`Search`] has seen.
+struct Run<S, C> {
+    nodes: Vec<Node<S, C>>,
+    best: HashMap<S, C>,
+    buckets: HashMap<u64, Vec<(S, C)>>,
+    frontier: Frontier<C>,
+    visited: Visited,
+    informed: bool,
+}
+
+impl<S: Clone + Hash + Eq, C: Cost> Run<S, C> {
+    /// Queue `node` unless it is pruned, dominated or already visited.
+    fn offer(&mut self, search: &mut Search<S, C>, node: Node<S, C>) {
+        if !search.prune_on_pop && (search.prune)(&node.state, node.cost) {
+            search.stats.pruned += 1;
+            return;
+        }
+        match self.visited {
+            Visited::FirstSeen if self.best.contains_key(&node.state) => return,
+            Visited::BestCost if self.best.get(&node.state).is_some_and(|c| *c <= node.cost) => {
+                return;
+            }
+            Visited::FirstSeen | Visited::BestCost => {
+                self.best.insert(node.state.clone(), node.cost);
+            }
+            Visited::Off => (),
+        }
+        if let Some(dominance) = search.dominance.as_ref() {
+            let bucket = self
+                .buckets
+                .entry((dominance.bucket)(&node.state))
+                .or_default();
+            let dominated = bucket.iter().any(|(other, cost)| {
+                *cost <= node.cost && (dominance.dominates)(other, &node.state)
+            });
+            if dominated {
+                search.stats.pruned += 1;
+                return;
+            }
+            bucket.push((node.state.clone(), node.cost));
+        }
+        let idx = self.nodes.len();
+        match &mut self.frontier {
+            Frontier::Fifo(queue) => queue.push_back(idx),
+            Frontier::Heap(heap) => {
+                let priority = if self.informed {
+                    node.cost + (search.heuristic)(&node.state)
+                } else {
+                    node.cost
+                };
+                heap.push(Reverse((priority, idx)));
+            }
+        }
+        self.nodes.push(node);
+        search.stats.generated += 1;
+        search.stats.peak_frontier = search.stats.peak_frontier.max(self.frontier.len());
+    }
+
+    /// States from a start to `idx`.
+    fn path(&self, idx: usize) -> Vec<S> {
+        let mut path = vec![];
+        let mut cur = Some(idx);
+        while let Some(idx) = cur {
+            path.push(self.nodes[idx].state.clone());
+            cur = self.nodes[idx].parent;
+        }
+        path.reverse();
+        path
+    }
+}
+
+impl<C: Ord> Frontier<C> {
+    fn pop(&mut self) -> Option<usize> {
+        match self {
+            Frontier::Fifo(queue) => queue.pop_front(),
+            Frontier::Heap(heap) => heap.pop().map(|Reverse((_, idx))| idx),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Frontier::Fifo(queue) => queue.len(),
+            Frontier::Heap(heap) => heap.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    fn garden() -> Grid<char> {
+        Grid::parse(&[
+            "##########",
+            "#.#......#",
+            "#.P.####P#",
+            "#.#...P#.#",
+            "##########",
+        ])
+    }
+
+    #[test]
+    fn test_bfs_distances() {
+        let distances = bfs_distances([0_i64], |n| {
+            [n - 1, n + 1].into_iter().filter(|v| v.abs() <= 3)
+        });
+        assert_eq!(7, distances.len());
+        assert_eq!(Some(&3), distances.get(&-3));
+    }
+
+    #[test]
+    fn test_grid_distances() {
+        let grid = garden();
+        let distances = grid_distances(&grid, [(1, 1)], |c| *c != '#');
+        assert_eq!(Some(0), distances[(1, 1)]);
+        assert_eq!(Some(2), distances[(2, 2)]);
+        assert_eq!(None, distances[(0, 0)]);
+    }
+
+    #[test]
+    fn test_sums_agree() {
+        let grid = garden();
+        let palms = grid.positions(|c| *c == 'P');
+        let dense = sum_grid_distances(&grid, &palms, |c| *c != '#');
+        let sparse = sum_distances(&palms, |n| {
+            grid.cardinals(*n)
+                .filter(|n| grid[*n] != '#')
+                .collect::<Vec<_>>()
+        });
+        for (pos, total) in dense.iter() {
+            assert_eq!(total.as_ref(), sparse.get(&pos));
+        }
+        let best = dense
+            .iter()
+            .filter(|(pos, _)| grid[*pos] == '.')
+            .filter_map(|(_, total)| *total)
+            .min();
+        assert_eq!(Some(12), best);
+    }
+
+    /// Moves along a number line where stepping left costs 1 and right costs 3.
+    fn line(n: &i64) -> [(i64, u64); 2] {
+        [(n - 1, 1), (n + 1, 3)]
+    }
+
+    #[test]
+    fn test_search_algorithms() {
+        let mut bfs = Search::new(line).goal(|n| *n == 4);
+        let found = bfs.bfs([0]).unwrap();
+        assert_eq!((12, vec![0, 1, 2, 3, 4]), (found.cost, found.path));
+
+        let mut dijkstra =
+            Search::new(|n: &i64| [(n - 1, 1), (n + 1, 3), (n + 2, 5)]).goal(|n| *n == 4);
+        assert_eq!(10, dijkstra.dijkstra([0]).unwrap().cost);
+
+        let mut astar = Search::new(|n: &i64| [(n - 1, 1), (n + 1, 3), (n + 2, 5)])
+            .goal(|n| *n == 4)
+            .heuristic(|n| (4 - n).max(0) as u64 * 5 / 2);
+        assert_eq!(10, astar.astar([0]).unwrap().cost);
+        assert!(astar.stats().expanded <= dijkstra.stats().expanded);
+    }
+
+    #[test]
+    fn test_search_pruning() {
+        // Walk to 3 collecting a token at 1; having the token dominates not having it.
+        let mut search = Search::new(|(n, token): &(i64, bool)| {
+            [n - 1, n + 1]
+                .into_iter()
+                .filter(|n| n.abs() <= 5)
+                .map(|n| ((n, *token || n == 1), 1_u32))
+                .collect::<Vec<_>>()
+        })
+        .goal(|(n, token)| *n == 3 && *token)
+        .dominance(|(n, _)| *n, |(_, a), (_, b)| *a || !b)
+        .prune(|(n, _), _| *n < -2);
+        let found = search.bfs([(0, false)]).unwrap();
+        assert_eq!(3, found.cost);
+        assert_eq!(&(3, true), found.goal());
+        assert!(search.stats().pruned > 0);
+        assert!(
+            search
+                .visited(Visited::Off)
+                .goal(|_| false)
+                .bfs([(0, false)])
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_search_prune_on_pop() {
+        // Count up from 0; the hook should only see states as they are expanded.
+        let seen = RefCell::new(vec![]);
+        let mut search = Search::new(|n: &u32| [(n + 1, 1_u32), (n + 2, 1)])
+            .goal(|n| *n == 6)
+            .prune(|n, _| {
+                seen.borrow_mut().push(*n);
+                n % 3 == 2
+            })
+            .prune_on_pop();
+        let found = search.bfs([0]).unwrap();
+        assert_eq!(vec![0, 1, 3, 4, 6], found.path);
+        assert_eq!(vec![0, 1, 2, 3, 4, 5], *seen.borrow());
+        assert_eq!(2, search.stats().pruned);
+    }
+}
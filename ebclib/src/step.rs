@@ -0,0 +1,323 @@
+//! Step through a simulation a tick at a time in the terminal.
+//!
+//! A quest implements [`Simulation`] and hands it to [`run`] when started as
+//! `koa2024qNN step <part>`. The screen is redrawn with ANSI escapes after each command
+//! line, and the cells that changed on the last tick are highlighted.
+
+use std::{
+    collections::VecDeque,
+    fmt::Write as _,
+    io::{self, Write},
+    str::FromStr,
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use crate::grid::Grid;
+
+/// Ticks kept for stepping back.
+pub const HISTORY: usize = 1000;
+
+/// Time between ticks while playing.
+const PLAY_DELAY: Duration = Duration::from_millis(150);
+
+const HIGHLIGHT: &str = "\x1b[7m";
+const RESET: &str = "\x1b[0m";
+const CLEAR: &str = "\x1b[H\x1b[2J";
+
+/// Something that advances in ticks. The simulation holds what never changes, like a
+/// map, and its `State` holds what does.
+pub trait Simulation {
+    type State: Clone;
+
+    /// The state at tick 0.
+    fn state(&self) -> Self::State;
+
+    /// The state a tick after `state`, or `None` once there's nothing left to do.
+    fn step(&self, state: &Self::State) -> Option<Self::State>;
+
+    /// A picture of `state`, one char per cell.
+    fn render(&self, state: &Self::State) -> Grid<char>;
+}
+
+/// A simulation with its recent states, so going back is just forgetting the latest.
+pub struct Stepper<S: Simulation> {
+    sim: S,
+    /// Oldest first; the last is the current state.
+    history: VecDeque<S::State>,
+    capacity: usize,
+    tick: usize,
+}
+
+impl<S: Simulation> Stepper<S> {
+    /// Keeps up to `capacity` states, at least the current one.
+    pub fn new(sim: S, capacity: usize) -> Self {
+        let history = VecDeque::from([sim.state()]);
+        Self {
+            sim,
+            history,
+            capacity: capacity.max(1),
+            tick: 0,
+        }
+    }
+
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    pub fn state(&self) -> &S::State {
+        self.history.back().unwrap()
+    }
+
+    /// The earliest tick that can still be gone back to.
+    pub fn oldest(&self) -> usize {
+        self.tick + 1 - self.history.len()
+    }
+
+    /// One tick on, or `false` if the simulation has finished.
+    pub fn forward(&mut self) -> bool {
+        let Some(next) = self.sim.step(self.state()) else {
+            return false;
+        };
+        if self.history.len() == self.capacity {
+            self.history.pop_front();
+        }
+        self.history.push_back(next);
+        self.tick += 1;
+        true
+    }
+
+    /// One tick back, or `false` if it's no longer in the history.
+    pub fn back(&mut self) -> bool {
+        if self.history.len() < 2 {
+            return false;
+        }
+        self.history.pop_back();
+        self.tick -= 1;
+        true
+    }
+
+    /// Go to tick `target`, back through the history or on by stepping. Going on stops
+    /// early if the simulation finishes.
+    pub fn jump(&mut self, target: usize) -> Result<(), String> {
+        if target < self.oldest() {
+            return Err(format!("Only back to tick {}", self.oldest()));
+        }
+        while self.tick > target {
+            self.back();
+        }
+        while self.tick < target {
+            if !self.forward() {
+                return Err(format!("Finished at tick {}", self.tick));
+            }
+        }
+        Ok(())
+    }
+
+    /// The current state drawn with the cells that changed since the last tick
+    /// highlighted.
+    pub fn frame(&self) -> String {
+        let current = self.sim.render(self.state());
+        let previous = self
+            .history
+            .len()
+            .checked_sub(2)
+            .map(|idx| self.sim.render(&self.history[idx]))
+            .filter(|prev| (prev.rows(), prev.cols()) == (current.rows(), current.cols()));
+        let mut frame = String::new();
+        for row in 0..current.rows() {
+            for col in 0..current.cols() {
+                let ch = current[(row, col)];
+                if previous.as_ref().is_some_and(|prev| prev[(row, col)] != ch) {
+                    let _ = write!(frame, "{HIGHLIGHT}{ch}{RESET}");
+                } else {
+                    frame.push(ch);
+                }
+            }
+            frame.push('\n');
+        }
+        frame
+    }
+}
+
+/// A line typed while stepping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Step,
+    Back,
+    Jump(usize),
+    Play,
+    Quit,
+}
+
+/// A blank line or `s` steps, `b` goes back, `j N` or just `N` jumps to tick N, `p`
+/// plays or pauses and `q` quits.
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let tick = s.strip_prefix('j').unwrap_or(s).trim();
+        match s {
+            "" | "s" => Ok(Command::Step),
+            "b" => Ok(Command::Back),
+            "p" => Ok(Command::Play),
+            "q" => Ok(Command::Quit),
+            _ => tick
+                .parse()
+                .map(Command::Jump)
+                .map_err(|_| format!("Unknown command {s:?}")),
+        }
+    }
+}
+
+/// The part asked for by `step <part>` on the command line, if any. Exits if it isn't
+/// one of the `parts` that can be stepped through.
+pub fn requested(parts: &[u8]) -> Option<u8> {
+    let mut args = std::env::args().skip(1);
+    if args.next()? != "step" {
+        return None;
+    }
+    match args.next().and_then(|part| part.parse().ok()) {
+        Some(part) if parts.contains(&part) => Some(part),
+        _ => {
+            eprintln!("Usage: step <part>, where part is one of {parts:?}");
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Step through `sim` until `q` or the end of input, then exit.
+pub fn run<S: Simulation>(sim: S) -> ! {
+    let mut stepper = Stepper::new(sim, HISTORY);
+    // Lines are read on their own thread so playing can check for one between ticks.
+    let (send, lines) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lines().map_while(Result::ok) {
+            if send.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    let mut playing = false;
+    let mut message = String::new();
+    loop {
+        draw(&stepper, playing, &message);
+        message.clear();
+        let line = if playing {
+            match lines.recv_timeout(PLAY_DELAY) {
+                Ok(line) => Some(line),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        } else {
+            match lines.recv() {
+                Ok(line) => Some(line),
+                Err(_) => break,
+            }
+        };
+        let Some(line) = line else {
+            playing = stepper.forward();
+            continue;
+        };
+        // Any line pauses a simulation that's playing, and a blank one does nothing else.
+        if playing && line.trim().is_empty() {
+            playing = false;
+            continue;
+        }
+        let was_playing = std::mem::take(&mut playing);
+        match line.parse() {
+            Ok(Command::Step) if !stepper.forward() => message = "Finished".to_string(),
+            Ok(Command::Step) => {}
+            Ok(Command::Back) if !stepper.back() => {
+                message = format!("Only back to tick {}", stepper.oldest())
+            }
+            Ok(Command::Back) => {}
+            Ok(Command::Jump(tick)) => {
+                if let Err(e) = stepper.jump(tick) {
+                    message = e;
+                }
+            }
+            Ok(Command::Play) => playing = !was_playing,
+            Ok(Command::Quit) => break,
+            Err(e) => message = e,
+        }
+    }
+    std::process::exit(0)
+}
+
+fn draw<S: Simulation>(stepper: &Stepper<S>, playing: bool, message: &str) {
+    let mut screen = format!("{CLEAR}{}", stepper.frame());
+    let _ = writeln!(
+        screen,
+        "Tick {}{}  [enter] step  b back  j N jump  p {}  q quit",
+        stepper.tick(),
+        if playing { " (playing)" } else { "" },
+        if playing { "pause" } else { "play" },
+    );
+    if !message.is_empty() {
+        let _ = writeln!(screen, "{message}");
+    }
+    let mut stdout = io::stdout().lock();
+    let _ = stdout.write_all(screen.as_bytes());
+    let _ = stdout.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A dot moving along a row, stopping at the end.
+    struct Walk(usize);
+
+    impl Simulation for Walk {
+        type State = usize;
+
+        fn state(&self) -> usize {
+            0
+        }
+
+        fn step(&self, state: &usize) -> Option<usize> {
+            (state + 1 < self.0).then_some(state + 1)
+        }
+
+        fn render(&self, state: &usize) -> Grid<char> {
+            let mut grid = Grid::new(1, self.0, '.');
+            grid[(0, *state)] = '#';
+            grid
+        }
+    }
+
+    #[test]
+    fn test_stepper() {
+        let mut stepper = Stepper::new(Walk(10), 3);
+        assert!(!stepper.back());
+        assert!(stepper.forward());
+        assert_eq!(
+            format!("{HIGHLIGHT}.{RESET}{HIGHLIGHT}#{RESET}........\n"),
+            stepper.frame()
+        );
+        assert_eq!(Ok(()), stepper.jump(5));
+        assert_eq!((5, 3), (*stepper.state(), stepper.oldest()));
+        assert_eq!(Err("Only back to tick 3".to_string()), stepper.jump(2));
+        assert!(stepper.back() && stepper.back());
+        assert!(!stepper.back());
+        assert_eq!("...#......\n", stepper.frame());
+        assert_eq!(Err("Finished at tick 9".to_string()), stepper.jump(20));
+        assert!(!stepper.forward());
+    }
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(Ok(Command::Step), "".parse());
+        assert_eq!(Ok(Command::Back), " b ".parse());
+        assert_eq!(Ok(Command::Jump(12)), "j 12".parse());
+        assert_eq!(Ok(Command::Jump(3)), "3".parse());
+        assert_eq!(Ok(Command::Play), "p".parse());
+        assert_eq!(
+            Err("Unknown command \"x\"".to_string()),
+            "x".parse::<Command>()
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! Enums written as single characters in puzzle input.
+//!
+//! `#[derive(Symbol)]` gives an enum `TryFrom<char>`, `Into<char>`, `Display` and an
+//! `ALL` slice. The helpers here say where in the input a bad character was.
+
+use crate::grid::Grid;
+
+pub use ebclib_derive::Symbol;
+
+/// Parse the character at `row` and `col`, naming the spot if it's unknown.
+pub fn parse_at<T: TryFrom<char, Error = String>>(
+    ch: char,
+    row: usize,
+    col: usize,
+) -> Result<T, String> {
+    T::try_from(ch).map_err(|e| format!("{e} at row {row} col {col}"))
+}
+
+/// Parse every character of a line.
+pub fn parse_line<T: TryFrom<char, Error = String>>(
+    line: impl IntoIterator<Item = char>,
+) -> Result<Vec<T>, String> {
+    line.into_iter()
+        .enumerate()
+        .map(|(col, ch)| T::try_from(ch).map_err(|e| format!("{e} at col {col}")))
+        .collect()
+}
+
+/// Parse a rectangle of characters into a grid.
+pub fn parse_grid<T, S>(lines: &[S]) -> Result<Grid<T>, String>
+where
+    T: TryFrom<char, Error = String>,
+    S: AsRef<str>,
+{
+    let rows = lines
+        .iter()
+        .enumerate()
+        .map(|(row, line)| {
+            line.as_ref()
+                .chars()
+                .enumerate()
+                .map(|(col, ch)| parse_at(ch, row, col))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<Vec<T>>, _>>()?;
+    if let Some(row) = rows.iter().position(|r| r.len() != rows[0].len()) {
+        return Err(format!("Row {row} is a different length to row 0"));
+    }
+    Ok(Grid::from_rows(rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Symbol)]
+    enum Tile {
+        #[symbol('.', 'S')]
+        Open,
+        #[symbol('#')]
+        Wall,
+    }
+
+    #[test]
+    fn test_derive() {
+        assert_eq!(Ok(Tile::Open), Tile::try_from('S'));
+        assert_eq!(Ok(Tile::Wall), Tile::try_from('#'));
+        assert_eq!(Err("Unknown char 'Q'".to_string()), Tile::try_from('Q'));
+        assert_eq!('.', char::from(Tile::Open));
+        assert_eq!("#.", format!("{}{}", Tile::Wall, Tile::Open));
+        assert_eq!(&[Tile::Open, Tile::Wall], Tile::ALL);
+    }
+
+    #[test]
+    fn test_positions() {
+        assert_eq!(Ok(vec![Tile::Wall, Tile::Open]), parse_line("#.".chars()));
+        let grid = parse_grid::<Tile, _>(&["#S", ".#"]).unwrap();
+        assert_eq!(Tile::Wall, grid[(1, 1)]);
+        let err = parse_grid::<Tile, _>(&["#.#", "..#", "..Q"]).unwrap_err();
+        assert_eq!("Unknown char 'Q' at row 2 col 2", err);
+        let err = parse_grid::<Tile, _>(&["#.", "..#"]).unwrap_err();
+        assert_eq!("Row 1 is a different length to row 0", err);
+    }
+}
@@ -0,0 +1,225 @@
+//! Single width tracks drawn as text, traced into the order they are travelled.
+
+use crate::{
+    grid::Grid,
+    parse::{Location, ParseError},
+};
+
+/// North, east, south and west as `(row, col)` steps.
+const HEADINGS: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+/// What tracing does where a track could go more than one way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Junctions {
+    /// Stop with an error.
+    Reject,
+    /// Go straight on where possible, so tracks can cross or run side by side.
+    Straight,
+}
+
+/// The cells of a track in order, starting at the start marker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Track {
+    cells: Vec<(usize, usize)>,
+    chars: Vec<char>,
+    closed: bool,
+}
+
+impl Track {
+    /// Trace a track drawn over several lines, padding short lines with spaces. Errors
+    /// point at the line and column of the bad cell, counting lines from the first.
+    pub fn parse<S: AsRef<str>>(
+        lines: &[S],
+        start: char,
+        path: &[char],
+        junctions: Junctions,
+    ) -> Result<Self, ParseError> {
+        Self::trace(&Grid::parse_padded(lines, ' '), start, path, junctions)
+    }
+
+    /// Follow the track from the only `start` cell over cells holding one of the `path`
+    /// chars. It sets off towards the first neighbour of the start in north, east,
+    /// south, west order; use [`Track::reversed`] to go the other way.
+    ///
+    /// A track ends when it gets back to the start, or at a dead end if the start only
+    /// had one neighbour. Other dead ends are errors, as are junctions unless
+    /// `junctions` says to go straight over them.
+    pub fn trace(
+        grid: &Grid<char>,
+        start: char,
+        path: &[char],
+        junctions: Junctions,
+    ) -> Result<Self, ParseError> {
+        let origin = match grid.positions(|c| *c == start)[..] {
+            [origin] => origin,
+            [] => return Err(ParseError::new(format!("a start marker {start:?}"))),
+            [_, second, ..] => {
+                return Err(at(grid, second, format!("only one start marker {start:?}")));
+            }
+        };
+        let on_track = |pos: Option<(usize, usize)>| {
+            pos.and_then(|pos| grid.get(pos))
+                .is_some_and(|c| *c == start || path.contains(c))
+        };
+        let exits = HEADINGS
+            .into_iter()
+            .filter(|heading| on_track(step(origin, *heading)))
+            .collect::<Vec<_>>();
+        let Some(mut heading) = exits.first().copied() else {
+            return Err(at(grid, origin, "the start to be next to the track"));
+        };
+        if exits.len() > 2 && junctions == Junctions::Reject {
+            return Err(at(grid, origin, "one way on, not a branch"));
+        }
+
+        let mut track = Self {
+            cells: vec![origin],
+            chars: vec![start],
+            closed: false,
+        };
+        let mut cur = origin;
+        // Each cell can be entered at most once from each side.
+        let limit = 4 * grid.rows() * grid.cols();
+        loop {
+            // The start's other exit is where a closed track comes back in.
+            let (dr, dc) = heading;
+            let turns = match cur == origin {
+                true => vec![],
+                false => [(-dc, dr), (dc, -dr)]
+                    .into_iter()
+                    .filter(|turn| on_track(step(cur, *turn)))
+                    .collect::<Vec<_>>(),
+            };
+            let straight = on_track(step(cur, heading));
+            heading = match (straight, &turns[..], junctions) {
+                (true, [], _) | (true, _, Junctions::Straight) => heading,
+                (false, [turn], _) => *turn,
+                (false, [], _) if exits.len() == 1 => return Ok(track),
+                (false, [], _) => return Err(at(grid, cur, "a way on, not a dead end")),
+                _ => return Err(at(grid, cur, "one way on, not a branch")),
+            };
+            cur = step(cur, heading).unwrap();
+            if cur == origin {
+                track.closed = true;
+                return Ok(track);
+            }
+            if track.cells.len() == limit {
+                return Err(at(grid, origin, "a track that comes back to the start"));
+            }
+            track.cells.push(cur);
+            track.chars.push(grid[cur]);
+        }
+    }
+
+    /// Number of cells, counting the start once.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Always false, a track has at least its start.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Positions in the order travelled, the start first.
+    pub fn cells(&self) -> &[(usize, usize)] {
+        &self.cells
+    }
+
+    /// Chars in the order travelled, the start marker first.
+    pub fn chars(&self) -> &[char] {
+        &self.chars
+    }
+
+    /// True if the track comes back round to the start.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// The same track travelled the other way. A closed track still begins at the start,
+    /// an open one begins at its far end.
+    pub fn reversed(&self) -> Self {
+        let mut reversed = self.clone();
+        let from = if self.closed { 1 } else { 0 };
+        reversed.cells[from..].reverse();
+        reversed.chars[from..].reverse();
+        reversed
+    }
+}
+
+/// An error underlining the cell at `(row, col)`.
+fn at(grid: &Grid<char>, (row, col): (usize, usize), expected: impl Into<String>) -> ParseError {
+    let text = (0..grid.cols()).map(|c| grid[(row, c)]).collect::<String>();
+    ParseError {
+        file: None,
+        location: Some(Location {
+            line: row + 1,
+            column: col + 1,
+            len: 1,
+            text: text.trim_end().to_string(),
+        }),
+        expected: expected.into(),
+    }
+}
+
+fn step((row, col): (usize, usize), (dr, dc): (isize, isize)) -> Option<(usize, usize)> {
+    Some((row.checked_add_signed(dr)?, col.checked_add_signed(dc)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATH: [char; 3] = ['+', '-', '='];
+
+    #[test]
+    fn test_closed_loop() {
+        let track =
+            Track::parse(&["S+===", "-   +", "=+=-+"], 'S', &PATH, Junctions::Reject).unwrap();
+        assert!(track.is_closed());
+        assert_eq!("S+===++-=+=-", track.chars().iter().collect::<String>());
+        let reversed = track.reversed();
+        assert_eq!("S-=+=-++===+", reversed.chars().iter().collect::<String>());
+        assert_eq!((1, 0), reversed.cells()[1]);
+    }
+
+    #[test]
+    fn test_side_by_side() {
+        let lines = ["S+= ===", "- +++ +", "-     +", "=+=-+=="];
+        let track = Track::parse(&lines, 'S', &PATH, Junctions::Reject);
+        assert_eq!(20, track.unwrap().len());
+    }
+
+    #[test]
+    fn test_junction() {
+        let lines = ["S+=+=+", "-  = -", "=+=-=+"];
+        let err = Track::parse(&lines, 'S', &PATH, Junctions::Reject).unwrap_err();
+        assert_eq!("one way on, not a branch", err.expected);
+        let at = err.location.unwrap();
+        assert_eq!((1, 4, "S+=+=+"), (at.line, at.column, at.text.as_str()));
+        let track = Track::parse(&lines, 'S', &PATH, Junctions::Straight).unwrap();
+        assert!(track.is_closed());
+        assert_eq!(14, track.len());
+        let err = Track::parse(&["S+=", "-+=", "=+-"], 'S', &PATH, Junctions::Reject);
+        assert_eq!(2, err.unwrap_err().location.unwrap().column);
+    }
+
+    #[test]
+    fn test_open_track() {
+        let track = Track::parse(&["  +", "S+="], 'S', &PATH, Junctions::Reject).unwrap();
+        assert!(!track.is_closed());
+        assert_eq!(vec![(1, 0), (1, 1), (1, 2), (0, 2)], track.cells());
+        assert_eq!(vec!['+', '=', '+', 'S'], track.reversed().chars());
+    }
+
+    #[test]
+    fn test_bad_tracks() {
+        assert!(Track::parse(&["S+=", "+ -", "==-"], 'X', &PATH, Junctions::Reject).is_err());
+        let err = Track::parse(&["S+=", "- =", "=+ "], 'S', &PATH, Junctions::Reject);
+        let err = err.unwrap_err();
+        assert_eq!("a way on, not a dead end", err.expected);
+        assert_eq!((2, 3), err.location.map(|at| (at.line, at.column)).unwrap());
+        assert!(Track::parse(&["=S=", " + "], 'S', &PATH, Junctions::Reject).is_err());
+        assert!(Track::parse(&["S  ", "   "], 'S', &PATH, Junctions::Reject).is_err());
+    }
+}
@@ -0,0 +1,330 @@
+//! Rooted trees parsed from `parent:child,child` notes.
+
+use std::collections::HashMap;
+
+use crate::{
+    intern::Interner,
+    parse::{self, ParseError},
+};
+
+/// A tree stored in an arena with node ids `0..len()`.
+///
+/// Names on the left of a note are branches and appear once in the tree. Names that
+/// only ever appear as children are leaves, and every mention is its own node, so
+/// markers like `@` can be repeated. Branches that can't be placed in a tree (more
+/// than one parent, part of a cycle or not reachable from the root) are left out along
+/// with everything below them, and reported by [`Tree::shared`], [`Tree::cycles`] and
+/// [`Tree::orphans`] instead of failing the parse.
+#[derive(Debug, Clone)]
+pub struct Tree {
+    names: Interner<String>,
+    nodes: Vec<Node>,
+    shared: Vec<usize>,
+    cycles: Vec<usize>,
+    orphans: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    name: usize,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    depth: usize,
+}
+
+impl Tree {
+    /// Parse one `parent:child,child` note per line, rooting the tree at `root`.
+    pub fn parse<S: AsRef<str>>(notes: &[S], root: &str) -> Result<Self, ParseError> {
+        let mut names = Interner::new();
+        let mut branches: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut parents: HashMap<usize, usize> = HashMap::new();
+        for line in parse::lines(notes) {
+            let (parent_span, children) = line.keyed()?;
+            let parent = names.intern(parent_span.as_str().to_string());
+            if branches.contains_key(&parent) {
+                return Err(parent_span.error("only one note per branch"));
+            }
+            let children = children
+                .iter()
+                .map(|c| names.intern(c.as_str().to_string()))
+                .collect::<Vec<_>>();
+            for child in children.iter() {
+                *parents.entry(*child).or_default() += 1;
+            }
+            branches.insert(parent, children);
+        }
+        let Some(root) = names.get(root).filter(|r| branches.contains_key(r)) else {
+            return Err(ParseError::new(format!("a note for the root {root}")));
+        };
+
+        let cycles = find_cycles(&branches, names.len());
+        let shared = (0..names.len())
+            .filter(|name| branches.contains_key(name) && parents.get(name) > Some(&1))
+            .collect();
+        let mut tree = Self {
+            names,
+            nodes: vec![Node {
+                name: root,
+                parent: None,
+                children: vec![],
+                depth: 0,
+            }],
+            shared,
+            cycles,
+            orphans: vec![],
+        };
+        let mut placed = vec![false; tree.names.len()];
+        placed[root] = true;
+        let mut to_visit = vec![0];
+        while let Some(node) = to_visit.pop() {
+            let name = tree.nodes[node].name;
+            let Some(children) = branches.get(&name) else {
+                continue;
+            };
+            for child in children.iter() {
+                if branches.contains_key(child) {
+                    if tree.shared.contains(child) || tree.cycles.contains(child) {
+                        continue;
+                    }
+                    placed[*child] = true;
+                }
+                let id = tree.nodes.len();
+                tree.nodes.push(Node {
+                    name: *child,
+                    parent: Some(node),
+                    children: vec![],
+                    depth: tree.nodes[node].depth + 1,
+                });
+                tree.nodes[node].children.push(id);
+                to_visit.push(id);
+            }
+        }
+        tree.orphans = (0..tree.names.len())
+            .filter(|name| branches.contains_key(name) && !placed[*name])
+            .collect();
+        tree.orphans.sort_unstable();
+        Ok(tree)
+    }
+
+    /// Number of nodes in the tree.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Always false, a tree has at least its root.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    pub fn root(&self) -> usize {
+        0
+    }
+
+    pub fn name(&self, node: usize) -> &str {
+        self.names.resolve(self.nodes[node].name)
+    }
+
+    pub fn parent(&self, node: usize) -> Option<usize> {
+        self.nodes[node].parent
+    }
+
+    pub fn children(&self, node: usize) -> &[usize] {
+        &self.nodes[node].children
+    }
+
+    /// Distance from the root, which has depth 0.
+    pub fn depth(&self, node: usize) -> usize {
+        self.nodes[node].depth
+    }
+
+    pub fn leaves(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.nodes.len()).filter(|node| self.nodes[*node].children.is_empty())
+    }
+
+    /// Node ids grouped by depth, the root alone in the first group.
+    pub fn by_depth(&self) -> Vec<Vec<usize>> {
+        let mut levels: Vec<Vec<usize>> = vec![];
+        for (id, node) in self.nodes.iter().enumerate() {
+            if levels.len() <= node.depth {
+                levels.resize(node.depth + 1, vec![]);
+            }
+            levels[node.depth].push(id);
+        }
+        levels
+    }
+
+    /// Nodes from the root down to `node`.
+    pub fn path(&self, node: usize) -> Vec<usize> {
+        let mut path = vec![node];
+        let mut cur = node;
+        while let Some(parent) = self.nodes[cur].parent {
+            path.push(parent);
+            cur = parent;
+        }
+        path.reverse();
+        path
+    }
+
+    /// Every root to leaf path.
+    pub fn paths(&self) -> impl Iterator<Item = Vec<usize>> + '_ {
+        self.leaves().map(|leaf| self.path(leaf))
+    }
+
+    /// The leaf matching `wanted` that is the only one at its depth. `None` if no
+    /// depth, or more than one depth, has exactly one matching leaf.
+    pub fn unique_depth_leaf(&self, wanted: impl Fn(&str) -> bool) -> Option<usize> {
+        let mut depths: HashMap<usize, Vec<usize>> = HashMap::new();
+        for leaf in self.leaves().filter(|leaf| wanted(self.name(*leaf))) {
+            depths.entry(self.depth(leaf)).or_default().push(leaf);
+        }
+        let mut unique = depths.into_values().filter(|leaves| leaves.len() == 1);
+        match (unique.next(), unique.next()) {
+            (Some(leaf), None) => Some(leaf[0]),
+            _ => None,
+        }
+    }
+
+    /// Branches left out because notes list them as a child more than once, whether or
+    /// not any of those notes are in the tree.
+    pub fn shared(&self) -> Vec<&str> {
+        self.shared
+            .iter()
+            .map(|n| self.names.resolve(*n).as_str())
+            .collect()
+    }
+
+    /// Branches left out because they are part of a cycle.
+    pub fn cycles(&self) -> Vec<&str> {
+        self.cycles
+            .iter()
+            .map(|n| self.names.resolve(*n).as_str())
+            .collect()
+    }
+
+    /// Branches that can't be reached from the root.
+    pub fn orphans(&self) -> Vec<&str> {
+        self.orphans
+            .iter()
+            .map(|n| self.names.resolve(*n).as_str())
+            .collect()
+    }
+}
+
+/// Every branch that can reach itself again.
+fn find_cycles(branches: &HashMap<usize, Vec<usize>>, names: usize) -> Vec<usize> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        New,
+        Open,
+        Done,
+    }
+    let mut marks = vec![Mark::New; names];
+    let mut in_cycle = vec![false; names];
+    let mut starts = branches.keys().copied().collect::<Vec<_>>();
+    starts.sort_unstable();
+    for start in starts {
+        if marks[start] != Mark::New {
+            continue;
+        }
+        // Iterative depth first search keeping the current path on a stack.
+        let mut stack = vec![(start, 0)];
+        marks[start] = Mark::Open;
+        while let Some((node, next)) = stack.pop() {
+            let children = branches.get(&node).map_or(&[][..], |c| c.as_slice());
+            if next == children.len() {
+                marks[node] = Mark::Done;
+                continue;
+            }
+            stack.push((node, next + 1));
+            let child = children[next];
+            match marks[child] {
+                Mark::New => {
+                    marks[child] = Mark::Open;
+                    stack.push((child, 0));
+                }
+                Mark::Open => {
+                    let from = stack.iter().position(|(n, _)| *n == child).unwrap();
+                    stack[from..].iter().for_each(|(n, _)| in_cycle[*n] = true);
+                }
+                Mark::Done => (),
+            }
+        }
+    }
+    (0..names).filter(|n| in_cycle[*n]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Tree {
+        Tree::parse(
+            &[
+                "RR:A,B,C", "A:D,E", "B:F,@", "C:G,H", "D:@", "E:@", "F:@", "G:@", "H:@",
+            ],
+            "RR",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_unique_depth_leaf() {
+        let tree = example();
+        let fruit = tree.unique_depth_leaf(|name| name == "@").unwrap();
+        let path = tree.path(fruit);
+        let names = path.iter().map(|n| tree.name(*n)).collect::<Vec<_>>();
+        assert_eq!(vec!["RR", "B", "@"], names);
+    }
+
+    #[test]
+    fn test_by_depth() {
+        let tree = example();
+        let sizes = tree.by_depth().iter().map(|l| l.len()).collect::<Vec<_>>();
+        assert_eq!(vec![1, 3, 6, 5], sizes);
+        assert_eq!(6, tree.paths().count());
+    }
+
+    #[test]
+    fn test_cycles_and_orphans() {
+        let tree = Tree::parse(
+            &[
+                "RR:A,B",
+                "A:@",
+                "B:BUG,@",
+                "BUG:ANT,BUG",
+                "ANT:BUG,@",
+                "X:Y",
+            ],
+            "RR",
+        )
+        .unwrap();
+        assert_eq!(vec!["BUG", "ANT"], tree.cycles());
+        assert_eq!(vec!["BUG"], tree.shared());
+        assert_eq!(vec!["BUG", "ANT", "X"], tree.orphans());
+        assert_eq!(2, tree.paths().count());
+    }
+
+    #[test]
+    fn test_shared() {
+        let tree = Tree::parse(
+            &[
+                "RR:A,B", "A:C,@", "B:C", "C:D,@", "D:@", "X:E", "Y:E", "E:@",
+            ],
+            "RR",
+        )
+        .unwrap();
+        assert_eq!(vec!["C", "E"], tree.shared());
+        assert!(tree.cycles().is_empty());
+        assert_eq!(vec!["C", "D", "X", "E", "Y"], tree.orphans());
+        let names = (0..tree.len()).map(|n| tree.name(n)).collect::<Vec<_>>();
+        assert_eq!(vec!["RR", "A", "B", "@"], names);
+    }
+
+    #[test]
+    fn test_bad_notes() {
+        let err = Tree::parse(&["RR:A", "", "RR:B"], "RR").unwrap_err();
+        assert_eq!(3, err.location.unwrap().line);
+        assert!(Tree::parse(&["RR A"], "RR").is_err());
+        assert!(Tree::parse(&["A:B"], "RR").is_err());
+    }
+}
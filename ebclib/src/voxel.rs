@@ -0,0 +1,291 @@
+//! Paths through 3D voxels written as direction tokens like `U5,R3,D2`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
+
+use crate::{
+    parse::{ParseError, Span},
+    search::bfs_distances,
+    symbol::Symbol,
+};
+
+/// A voxel as `[x, y, z]`.
+pub type Voxel = [i64; 3];
+
+/// The six face neighbour offsets.
+pub const DIRECTIONS: [Voxel; 6] = [
+    [0, 0, 1],
+    [0, 0, -1],
+    [0, 1, 0],
+    [0, -1, 0],
+    [1, 0, 0],
+    [-1, 0, 0],
+];
+
+/// The letters naming each direction in a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Symbol)]
+pub enum Direction {
+    #[symbol('U')]
+    Up,
+    #[symbol('D')]
+    Down,
+    #[symbol('L')]
+    Left,
+    #[symbol('R')]
+    Right,
+    #[symbol('F')]
+    Forward,
+    #[symbol('B')]
+    Back,
+}
+
+impl Direction {
+    /// `U`/`D` along z, `L`/`R` along y and `F`/`B` along x.
+    pub fn offset(self) -> Voxel {
+        match self {
+            Self::Up => [0, 0, 1],
+            Self::Down => [0, 0, -1],
+            Self::Left => [0, 1, 0],
+            Self::Right => [0, -1, 0],
+            Self::Forward => [1, 0, 0],
+            Self::Back => [-1, 0, 0],
+        }
+    }
+}
+
+/// A straight move of `len` voxels along one axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub direction: Voxel,
+    pub len: i64,
+}
+
+impl Step {
+    /// Total movement of the step.
+    pub fn offset(&self) -> Voxel {
+        self.direction.map(|v| v * self.len)
+    }
+}
+
+/// A [`Direction`] letter then a length.
+impl FromStr for Step {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let Some(letter) = s.chars().next() else {
+            return Err("Empty step".to_string());
+        };
+        let direction = Direction::try_from(letter)
+            .map_err(|e| format!("{e} in {s}"))?
+            .offset();
+        let len = s[letter.len_utf8()..]
+            .parse()
+            .map_err(|e| format!("Bad length in {s}: {e}"))?;
+        Ok(Self { direction, len })
+    }
+}
+
+/// Parse a comma separated list of steps.
+pub fn parse_path(line: Span) -> Result<Vec<Step>, ParseError> {
+    line.list(',')
+}
+
+/// Where a path ends up when started from `start`.
+pub fn walk(start: Voxel, steps: &[Step]) -> Voxel {
+    steps
+        .iter()
+        .fold(start, |cur, step| add(cur, step.offset()))
+}
+
+fn add(a: Voxel, b: Voxel) -> Voxel {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+/// Smallest box holding a set of voxels, both corners included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: Voxel,
+    pub max: Voxel,
+}
+
+impl Bounds {
+    pub fn contains(&self, voxel: &Voxel) -> bool {
+        (0..3).all(|axis| (self.min[axis]..=self.max[axis]).contains(&voxel[axis]))
+    }
+
+    /// Number of voxels along each axis.
+    pub fn extent(&self) -> [u64; 3] {
+        [0, 1, 2].map(|axis| self.min[axis].abs_diff(self.max[axis]) + 1)
+    }
+}
+
+/// A set of filled voxels that can be walked between face neighbours.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VoxelSet {
+    cells: HashSet<Voxel>,
+}
+
+impl VoxelSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, voxel: Voxel) -> bool {
+        self.cells.insert(voxel)
+    }
+
+    pub fn contains(&self, voxel: &Voxel) -> bool {
+        self.cells.contains(voxel)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Voxel> {
+        self.cells.iter()
+    }
+
+    /// Fill every voxel passed through following `steps` from `start`, not including
+    /// `start` itself. Returns where the path ends.
+    pub fn trace(&mut self, start: Voxel, steps: &[Step]) -> Voxel {
+        let mut cur = start;
+        for step in steps {
+            for _ in 0..step.len {
+                cur = add(cur, step.direction);
+                self.cells.insert(cur);
+            }
+        }
+        cur
+    }
+
+    /// `None` for an empty set.
+    pub fn bounds(&self) -> Option<Bounds> {
+        let mut cells = self.cells.iter();
+        let first = *cells.next()?;
+        Some(cells.fold(
+            Bounds {
+                min: first,
+                max: first,
+            },
+            |bounds, v| Bounds {
+                min: [0, 1, 2].map(|axis| bounds.min[axis].min(v[axis])),
+                max: [0, 1, 2].map(|axis| bounds.max[axis].max(v[axis])),
+            },
+        ))
+    }
+
+    /// Filled face neighbours of `voxel`.
+    pub fn neighbours(&self, voxel: Voxel) -> impl Iterator<Item = Voxel> + '_ {
+        DIRECTIONS
+            .into_iter()
+            .map(move |d| add(voxel, d))
+            .filter(|next| self.cells.contains(next))
+    }
+
+    /// Fewest moves from the nearest start to every filled voxel it can reach.
+    pub fn distances(&self, starts: impl IntoIterator<Item = Voxel>) -> HashMap<Voxel, usize> {
+        bfs_distances(starts, |v| self.neighbours(*v))
+    }
+
+    /// The candidate with the smallest total distance to every target, and that total.
+    /// Builds one distance field per target rather than searching from each candidate.
+    /// Candidates that can't reach every target are skipped; ties go to the first.
+    pub fn best_meeting_point(
+        &self,
+        targets: &[Voxel],
+        candidates: impl IntoIterator<Item = Voxel>,
+    ) -> Option<(Voxel, usize)> {
+        let fields = targets
+            .iter()
+            .map(|target| self.distances([*target]))
+            .collect::<Vec<_>>();
+        let mut best: Option<(Voxel, usize)> = None;
+        for candidate in candidates {
+            let total = fields
+                .iter()
+                .map(|field| field.get(&candidate))
+                .sum::<Option<usize>>();
+            if let Some(total) = total
+                && best.is_none_or(|(_, cur)| total < cur)
+            {
+                best = Some((candidate, total));
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let steps = parse_path(Span::new("U5,R3,D2")).unwrap();
+        assert_eq!(
+            Step {
+                direction: [0, -1, 0],
+                len: 3
+            },
+            steps[1]
+        );
+        assert_eq!([0, -3, 3], walk([0, 0, 0], &steps));
+        assert_eq!([1, 0, 0], Direction::Forward.offset());
+        assert_eq!([0, 0, -1], Direction::Down.offset());
+        let err = parse_path(Span::new("U5,X3")).unwrap_err();
+        assert_eq!("Step (Unknown char 'X' in X3)", err.expected);
+        assert_eq!(4, err.location.unwrap().column);
+        assert!(parse_path(Span::new("U5,R")).is_err());
+    }
+
+    #[test]
+    fn test_direction_offsets() {
+        let offsets = "UDLRFB"
+            .chars()
+            .map(|letter| Direction::try_from(letter).unwrap().offset())
+            .collect::<Vec<_>>();
+        assert_eq!(DIRECTIONS.to_vec(), offsets);
+    }
+
+    #[test]
+    fn test_trace_and_bounds() {
+        let mut voxels = VoxelSet::new();
+        for line in ["U5,R3,D2,L5,U4,R5,D2", "U6,L1,D2,R3,U2,L1"] {
+            voxels.trace([0, 0, 0], &parse_path(Span::new(line)).unwrap());
+        }
+        assert_eq!(32, voxels.len());
+        let bounds = voxels.bounds().unwrap();
+        assert_eq!([0, -3, 1], bounds.min);
+        assert_eq!([0, 2, 7], bounds.max);
+        assert_eq!([1, 6, 7], bounds.extent());
+        assert!(bounds.contains(&[0, 0, 7]));
+        assert!(!bounds.contains(&[0, 0, 0]));
+    }
+
+    #[test]
+    fn test_best_meeting_point() {
+        let mut voxels = VoxelSet::new();
+        let leaves = ["U3,R2", "U3,L2", "U1,F1"].map(|line| {
+            let steps = parse_path(Span::new(line)).unwrap();
+            voxels.trace([0, 0, 0], &steps)
+        });
+        let trunk = (1..=3).map(|z| [0, 0, z]);
+        assert_eq!(
+            Some(([0, 0, 3], 4)),
+            voxels.best_meeting_point(&leaves[..2], trunk.clone())
+        );
+        assert_eq!(
+            Some(([0, 0, 3], 7)),
+            voxels.best_meeting_point(&leaves, trunk)
+        );
+        assert_eq!(None, voxels.best_meeting_point(&leaves, [[9, 9, 9]]));
+    }
+}
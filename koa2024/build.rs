@@ -0,0 +1,33 @@
+//! Index every quest binary by its `#[ebclib::quest(...)]` attribute, so the runner
+//! can't miss one.
+
+use std::{env, fs, path::Path};
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/bin");
+    let mut bins = fs::read_dir("src/bin")
+        .expect("Can't read src/bin")
+        .map(|entry| entry.expect("Can't read src/bin").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+        .collect::<Vec<_>>();
+    bins.sort();
+
+    let mut quests = vec![];
+    for path in bins {
+        let name = path.file_stem().unwrap().to_string_lossy().to_string();
+        let source = fs::read_to_string(&path).expect("Can't read quest source");
+        let quest = ebclib_quest::in_file(&name, &source)
+            .unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+        quests.push(format!(
+            "    ebclib::registry::QuestId {{ event: {:?}, year: {}, number: {} }},\n",
+            quest.event, quest.year, quest.number
+        ));
+    }
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("quests.rs");
+    let index = format!(
+        "/// Every quest binary, in order.\npub const QUESTS: &[ebclib::registry::QuestId] = &[\n{}];\n",
+        quests.concat()
+    );
+    fs::write(out, index).expect("Can't write quest index");
+}
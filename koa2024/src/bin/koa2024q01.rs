@@ -1,59 +1,52 @@
+use ebclib::symbol::{self, Symbol};
 use puzlib::read_line;
 
-fn main() {
-    let input1 = read_line("koa2024/inputs/quest01.1.txt");
+#[ebclib::quest(event = "koa", year = 2024, number = 1)]
+fn main() -> Result<(), String> {
+    let input1 = symbol::parse_line(read_line("koa2024/inputs/quest01.1.txt"))?;
     println!("Part 1: {}", part_one(&input1));
-    let input2 = read_line("koa2024/inputs/quest01.2.txt");
+    let input2 = symbol::parse_line(read_line("koa2024/inputs/quest01.2.txt"))?;
     println!("Part 2: {}", part_two(&input2));
-    let input3 = read_line("koa2024/inputs/quest01.3.txt");
+    let input3 = symbol::parse_line(read_line("koa2024/inputs/quest01.3.txt"))?;
     println!("Part 3: {}", part_three(&input3));
+    Ok(())
 }
 
-fn part_one(input: &[char]) -> u32 {
+fn part_one(input: &[Creature]) -> u32 {
     potion_calculator(input, 1)
 }
 
-fn part_two(input: &[char]) -> u32 {
+fn part_two(input: &[Creature]) -> u32 {
     potion_calculator(input, 2)
 }
-fn part_three(input: &[char]) -> u32 {
+fn part_three(input: &[Creature]) -> u32 {
     potion_calculator(input, 3)
 }
 
-fn potion_calculator(creatures: &[char], mob_size: usize) -> u32 {
+fn potion_calculator(creatures: &[Creature], mob_size: usize) -> u32 {
     creatures
         .chunks(mob_size)
         .map(|w| {
-            let mob = w
-                .iter()
-                .filter_map(|c| std::convert::Into::<Creature>::into(*c).value())
-                .collect::<Vec<_>>();
+            let mob = w.iter().filter_map(|c| c.value()).collect::<Vec<_>>();
             mob.iter().sum::<u32>() + (mob.len().saturating_sub(1) * mob.len()) as u32
         })
         .sum::<u32>()
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Symbol)]
 enum Creature {
+    #[symbol('A')]
     A,
+    #[symbol('B')]
     B,
+    #[symbol('C')]
     C,
+    #[symbol('D')]
     D,
+    #[symbol('x')]
     Empty,
 }
 
-impl From<char> for Creature {
-    fn from(value: char) -> Self {
-        match value {
-            'A' => Self::A,
-            'B' => Self::B,
-            'C' => Self::C,
-            'D' => Self::D,
-            'x' => Self::Empty,
-            _ => unreachable!("Unknown character {value}"),
-        }
-    }
-}
-
 impl Creature {
     fn value(&self) -> Option<u32> {
         match self {
@@ -72,19 +65,25 @@ mod tests {
 
     #[test]
     fn test_part_one() {
-        let creatures = "ABBAC".chars().collect::<Vec<_>>();
+        let creatures = symbol::parse_line("ABBAC".chars()).unwrap();
         assert_eq!(5, part_one(&creatures));
     }
 
     #[test]
     fn test_part_two() {
-        let creatures = "AxBCDDCAxD".chars().collect::<Vec<_>>();
+        let creatures = symbol::parse_line("AxBCDDCAxD".chars()).unwrap();
         assert_eq!(28, part_two(&creatures));
     }
 
+    #[test]
+    fn test_unknown_creature() {
+        let err = symbol::parse_line::<Creature>("ABxQ".chars()).unwrap_err();
+        assert_eq!("Unknown char 'Q' at col 3", err);
+    }
+
     #[test]
     fn test_part_three() {
-        let creatures = "xBxAAABCDxCC".chars().collect::<Vec<_>>();
+        let creatures = symbol::parse_line("xBxAAABCDxCC".chars()).unwrap();
         assert_eq!(30, part_three(&creatures));
     }
 }
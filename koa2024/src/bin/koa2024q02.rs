@@ -1,28 +1,41 @@
 use std::collections::{HashMap, HashSet};
 
-use puzlib::read_lines;
+use ebclib::parse::{self, ParseError, Source};
 
-fn main() {
-    println!("Part 1: {}", part_one());
-    println!("Part 2: {}", part_two());
-    let inscriptions = read_lines("koa2024/inputs/quest02.3.txt");
-    println!("Part 3: {}", part_three(inscriptions));
+#[ebclib::quest(event = "koa", year = 2024, number = 2)]
+fn main() -> Result<(), ParseError> {
+    let (runes, inscriptions) = Source::read("koa2024/inputs/quest02.1.txt")?.parse_with(parse)?;
+    println!("Part 1: {}", part_one(&runes, &inscriptions));
+
+    let (runes, inscriptions) = Source::read("koa2024/inputs/quest02.2.txt")?.parse_with(parse)?;
+    println!("Part 2: {}", part_two(&runes, &inscriptions));
+
+    let (runes, inscriptions) = Source::read("koa2024/inputs/quest02.3.txt")?.parse_with(parse)?;
+    println!("Part 3: {}", part_three(&runes, &inscriptions));
+    Ok(())
 }
 
-fn part_one() -> usize {
-    let mut input = read_lines("koa2024/inputs/quest02.1.txt");
-    let inscription = input.pop().unwrap();
-    let runes = Runes::new(input.pop().unwrap().split_once(':').unwrap().1);
-    runes.find_words(inscription)
+/// A `WORDS:` line of runes, then the inscriptions.
+fn parse<S: AsRef<str>>(lines: &[S]) -> Result<(Runes, Vec<String>), ParseError> {
+    let (header, inscriptions) = parse::headed(lines)?;
+    let (_, runes) = header.keyed()?;
+    let runes = Runes::new(runes.iter().map(|rune| rune.as_str()));
+    let inscriptions = inscriptions
+        .iter()
+        .map(|line| line.as_str().to_string())
+        .collect();
+    Ok((runes, inscriptions))
 }
 
-fn part_two() -> usize {
-    let mut inscriptions = read_lines("koa2024/inputs/quest02.2.txt");
-    let runes = Runes::new(inscriptions.remove(0).split_once(':').unwrap().1);
+fn part_one(runes: &Runes, inscriptions: &[String]) -> usize {
+    inscriptions.iter().map(|i| runes.find_words(i)).sum()
+}
+
+fn part_two(runes: &Runes, inscriptions: &[String]) -> usize {
     inscriptions.iter().map(|i| runes.find_chars(i)).sum()
 }
-fn part_three(mut inscriptions: Vec<String>) -> usize {
-    let runes = Runes::new(inscriptions.remove(0).split_once(':').unwrap().1);
+
+fn part_three(runes: &Runes, inscriptions: &[String]) -> usize {
     let mut matrix: HashMap<(i64, i64), char> = HashMap::new();
     for (row, line) in inscriptions.iter().enumerate() {
         for (col, char) in line.chars().enumerate() {
@@ -39,9 +52,9 @@ struct Runes {
 }
 
 impl Runes {
-    fn new<T: AsRef<str>>(runes: T) -> Self {
+    fn new<'a>(runes: impl IntoIterator<Item = &'a str>) -> Self {
         Self {
-            runes: runes.as_ref().split(',').map(|s| s.to_string()).collect(),
+            runes: runes.into_iter().map(|s| s.to_string()).collect(),
         }
     }
 
@@ -143,7 +156,7 @@ mod tests {
 
     #[test]
     fn test_one() {
-        let runes = Runes::new("THE,OWE,MES,ROD,HER");
+        let runes = Runes::new(["THE", "OWE", "MES", "ROD", "HER"]);
         assert_eq!(
             4_usize,
             runes.find_words("AWAKEN THE POWER ADORNED WITH THE FLAMES BRIGHT IRE")
@@ -158,18 +171,20 @@ mod tests {
 
     #[test]
     fn test_two() {
-        let runes = Runes::new("THE,OWE,MES,ROD,HER,QAQ");
+        let runes = Runes::new(["THE", "OWE", "MES", "ROD", "HER", "QAQ"]);
         assert_eq!(5, runes.find_chars("UQAQAQU"))
     }
 
     #[test]
     fn test_three() {
-        let text = vec![
-            "WORDS:THE,OWE,MES,ROD,RODEO".to_string(),
-            "HELWORLT".to_string(),
-            "ENIGWDXL".to_string(),
-            "TRODEOAL".to_string(),
-        ];
-        assert_eq!(10, part_three(text));
+        let (runes, inscriptions) = parse(&[
+            "WORDS:THE,OWE,MES,ROD,RODEO",
+            "",
+            "HELWORLT",
+            "ENIGWDXL",
+            "TRODEOAL",
+        ])
+        .unwrap();
+        assert_eq!(10, part_three(&runes, &inscriptions));
     }
 }
@@ -3,9 +3,20 @@ use std::{
     sync::LazyLock,
 };
 
+use ebclib::{
+    grid::Grid,
+    render::{self, Animation, Image},
+    step::{self, Simulation},
+};
 use puzlib::{Dir, Vec2D, read_grid};
 
+#[ebclib::quest(event = "koa", year = 2024, number = 3)]
 fn main() {
+    if let Some(part) = step::requested(&[1, 2, 3]) {
+        let input = read_grid(&format!("koa2024/inputs/quest03.{part}.txt"));
+        step::run(Erosion::new(input, part == 3));
+    }
+
     let input = read_grid("koa2024/inputs/quest03.1.txt");
     println!("Part 1: {}", part_one(input));
 
@@ -28,13 +39,17 @@ fn part_one(grid: Vec<Vec<char>>) -> usize {
 fn part_three(grid: Vec<Vec<char>>) -> usize {
     let ((rows, cols), mut map) = get_grid(grid);
     let mut next_step = 1;
-    #[cfg(test)]
-    show_grid(rows, cols, &map);
-    while step_all_ways(rows, cols, &mut map, next_step) {
-        #[cfg(test)]
-        show_grid(rows, cols, &map);
+    let rendering = render::render_dir().is_some();
+    let mut frames = vec![];
+    while {
+        if rendering {
+            frames.push(depths(rows, cols, &map));
+        }
+        step_all_ways(rows, cols, &mut map, next_step)
+    } {
         next_step += 1;
     }
+    render_erosion(&frames);
     map.values().sum()
 }
 
@@ -125,15 +140,75 @@ fn get_grid(grid: Vec<Vec<char>>) -> ((i64, i64), HashMap<(i64, i64), usize>) {
     ((rows as i64, cols as i64), map)
 }
 
-#[cfg(test)]
-fn show_grid(rows: i64, cols: i64, map: &HashMap<(i64, i64), usize>) {
-    for row in 0..rows {
-        for col in 0..cols {
-            print!("{}", map[&(row, col)]);
+/// Digging a layer a tick, with depths shown in base 36.
+struct Erosion {
+    rows: i64,
+    cols: i64,
+    start: HashMap<(i64, i64), usize>,
+    all_ways: bool,
+}
+
+impl Erosion {
+    fn new(grid: Vec<Vec<char>>, all_ways: bool) -> Self {
+        let ((rows, cols), start) = get_grid(grid);
+        Self {
+            rows,
+            cols,
+            start,
+            all_ways,
         }
-        println!();
     }
-    println!();
+}
+
+impl Simulation for Erosion {
+    /// The next layer to dig and the depths so far.
+    type State = (usize, HashMap<(i64, i64), usize>);
+
+    fn state(&self) -> Self::State {
+        (1, self.start.clone())
+    }
+
+    fn step(&self, (next_step, map): &Self::State) -> Option<Self::State> {
+        let mut map = map.clone();
+        let dug = if self.all_ways {
+            step_all_ways(self.rows, self.cols, &mut map, *next_step)
+        } else {
+            step(self.rows, self.cols, &mut map, *next_step)
+        };
+        dug.then_some((next_step + 1, map))
+    }
+
+    fn render(&self, (_, map): &Self::State) -> Grid<char> {
+        depths(self.rows, self.cols, map).map(|depth| match depth {
+            0 => '.',
+            depth => char::from_digit(*depth as u32, 36).unwrap_or('+'),
+        })
+    }
+}
+
+fn depths(rows: i64, cols: i64, map: &HashMap<(i64, i64), usize>) -> Grid<usize> {
+    let mut grid = Grid::new(rows as usize, cols as usize, 0);
+    for ((row, col), depth) in map.iter() {
+        grid[(*row as usize, *col as usize)] = *depth;
+    }
+    grid
+}
+
+/// Save the digging as `quest03.gif` in the `EBC_RENDER` directory, if it's set.
+fn render_erosion(frames: &[Grid<usize>]) {
+    let Some(dir) = render::render_dir() else {
+        return;
+    };
+    let deepest = frames.iter().flat_map(|f| f.iter()).map(|(_, d)| *d).max();
+    let mut animation = Animation::new();
+    for frame in frames {
+        animation.push(Image::from_grid(frame, 4, |depth| {
+            render::shade(*depth, deepest.unwrap_or(1))
+        }));
+    }
+    if let Err(e) = animation.save_gif(dir.join("quest03.gif"), 25) {
+        eprintln!("{e}");
+    }
 }
 
 #[cfg(test)]
@@ -1,25 +1,28 @@
-use ebclib::Median;
-use puzlib::read_lines;
-
-fn main() {
-    let input = read_lines("koa2024/inputs/quest04.1.txt")
-        .iter()
-        .map(|v| v.parse::<i64>().unwrap())
-        .collect::<Vec<_>>();
+use ebclib::{
+    Median,
+    parse::{ParseError, Source},
+};
+
+#[ebclib::quest(event = "koa", year = 2024, number = 4)]
+fn main() -> Result<(), ParseError> {
+    let input = read_nails("koa2024/inputs/quest04.1.txt")?;
     println!("Part 1: {}", part_one(&input));
 
-    let input = read_lines("koa2024/inputs/quest04.2.txt")
-        .iter()
-        .map(|v| v.parse::<i64>().unwrap())
-        .collect::<Vec<_>>();
+    let input = read_nails("koa2024/inputs/quest04.2.txt")?;
     println!("Part 2: {}", part_one(&input));
 
-    let mut input = read_lines("koa2024/inputs/quest04.3.txt")
-        .iter()
-        .map(|v| v.parse::<i64>().unwrap())
-        .collect::<Vec<_>>();
+    let mut input = read_nails("koa2024/inputs/quest04.3.txt")?;
     input.sort();
     println!("Part 3: {}", part_three(&input));
+    Ok(())
+}
+
+/// One nail height per line.
+fn read_nails(path: &str) -> Result<Vec<i64>, ParseError> {
+    Source::read(path)?
+        .lines()
+        .map(|line| line.trim().parse())
+        .collect()
 }
 
 fn part_one(nails: &[i64]) -> i64 {
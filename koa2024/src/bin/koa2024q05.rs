@@ -1,32 +1,42 @@
-use puzlib::read_lines;
-use std::collections::{HashMap, HashSet, VecDeque};
+use ebclib::{
+    cycle::Detector,
+    grid::Grid,
+    parse::{self, ParseError, Source},
+    ring,
+    step::{self, Simulation},
+};
+use std::collections::{HashMap, VecDeque};
+
+#[ebclib::quest(event = "koa", year = 2024, number = 5)]
+fn main() -> Result<(), ParseError> {
+    if let Some(part) = step::requested(&[1, 2, 3]) {
+        let path = format!("koa2024/inputs/quest05.{part}.txt");
+        step::run(Dancing(Source::read(&path)?.parse_with(Dance::new)?));
+    }
 
-fn main() {
-    let input = read_lines("koa2024/inputs/quest05.1.txt");
-    println!("Part 1: {}", part_one(input));
+    let dance = Source::read("koa2024/inputs/quest05.1.txt")?.parse_with(Dance::new)?;
+    println!("Part 1: {}", part_one(dance));
 
-    let input = read_lines("koa2024/inputs/quest05.2.txt");
-    println!("Part 2: {}", part_two(input));
+    let dance = Source::read("koa2024/inputs/quest05.2.txt")?.parse_with(Dance::new)?;
+    println!("Part 2: {}", part_two(dance));
 
-    let input = read_lines("koa2024/inputs/quest05.3.txt");
-    println!("Part 3: {}", part_three(input));
+    let dance = Source::read("koa2024/inputs/quest05.3.txt")?.parse_with(Dance::new)?;
+    println!("Part 3: {}", part_three(dance));
+    Ok(())
 }
 
-fn part_one(input: Vec<String>) -> u64 {
-    let mut dance = Dance::new(&input);
+fn part_one(mut dance: Dance) -> u64 {
     (0..9).for_each(|idx| {
         dance.step(idx % dance.columns.len());
     });
     dance.step(9 % dance.columns.len())
 }
 
-fn part_two(input: Vec<String>) -> u64 {
-    let mut dance = Dance::new(&input);
+fn part_two(mut dance: Dance) -> u64 {
     dance.dance_until(2024)
 }
 
-fn part_three(input: Vec<String>) -> u64 {
-    let mut dance = Dance::new(&input);
+fn part_three(dance: Dance) -> u64 {
     dance.dance_forever()
 }
 
@@ -36,22 +46,25 @@ struct Dance {
 }
 
 impl Dance {
-    fn new<S: AsRef<str>>(input: &[S]) -> Self {
+    fn new<S: AsRef<str>>(input: &[S]) -> Result<Self, ParseError> {
         let mut columns = vec![VecDeque::new(); 4];
-        for row in input {
-            for (col, ch) in row.as_ref().split(' ').enumerate() {
-                columns[col].push_back(ch.parse().unwrap());
+        for row in parse::lines(input) {
+            let numbers = row.numbers::<u64>()?;
+            if numbers.len() > columns.len() {
+                return Err(row.error("at most 4 columns"));
+            }
+            for (column, number) in columns.iter_mut().zip(numbers) {
+                column.push_back(number);
             }
         }
-        Self { columns }
+        Ok(Self { columns })
     }
 
     fn step(&mut self, clapper_col: usize) -> u64 {
         let clapper = self.columns[clapper_col].pop_front().unwrap();
         let next_column = (clapper_col + 1) % self.columns.len();
-        let next_column_len = 2 * self.columns[next_column].len();
-        let remainder = (clapper as usize - 1) % next_column_len;
-        let position = remainder.min(next_column_len - remainder);
+        // The clapper walks down one side of the column and back up the other.
+        let position = ring::bounce(clapper as usize - 1, self.columns[next_column].len());
         self.columns[next_column].insert(position, clapper);
         self.columns
             .iter()
@@ -71,14 +84,64 @@ impl Dance {
         }
     }
 
-    fn dance_forever(&mut self) -> u64 {
-        let mut states = HashSet::new();
+    fn dance_forever(&self) -> u64 {
+        // The next clapper depends on the round as well as the columns. Each state is
+        // scored as it's seen, so the dance is only danced once.
+        let mut dance = Dance {
+            columns: self.columns.clone(),
+        };
+        let mut detector = Detector::new();
         let mut round = 0;
-        while states.insert(self.columns.clone()) {
-            self.step(round % self.columns.len());
-            round += 1;
+        let mut best = 0;
+        loop {
+            best = best.max(calc(&dance.columns));
+            if detector.observe((round, dance.columns.clone())).is_some() {
+                return best;
+            }
+            dance.step(round);
+            round = (round + 1) % dance.columns.len();
         }
-        states.iter().map(|v| calc(v)).max().unwrap()
+    }
+}
+
+/// A round a tick, with the number shouted above the columns.
+struct Dancing(Dance);
+
+impl Simulation for Dancing {
+    /// The rounds danced and the columns.
+    type State = (usize, Vec<VecDeque<u64>>);
+
+    fn state(&self) -> Self::State {
+        (0, self.0.columns.clone())
+    }
+
+    fn step(&self, (round, columns): &Self::State) -> Option<Self::State> {
+        let mut dance = Dance {
+            columns: columns.clone(),
+        };
+        dance.step(round % columns.len());
+        Some((round + 1, dance.columns))
+    }
+
+    fn render(&self, (_, columns): &Self::State) -> Grid<char> {
+        let width = columns
+            .iter()
+            .flatten()
+            .map(|number| number.to_string().len() + 1)
+            .max()
+            .unwrap_or(1);
+        let rows = columns.iter().map(VecDeque::len).max().unwrap_or(0);
+        let mut lines = vec![format!("Shout {}", calc(columns)), String::new()];
+        lines.extend((0..rows).map(|row| {
+            columns
+                .iter()
+                .map(|column| match column.get(row) {
+                    Some(number) => format!("{number:>width$}"),
+                    None => " ".repeat(width),
+                })
+                .collect::<String>()
+        }));
+        Grid::parse_padded(&lines, ' ')
     }
 }
 
@@ -94,7 +157,7 @@ mod tests {
 
     #[test]
     fn test_one() {
-        let mut dance = Dance::new(&["2 3 4 5", "3 4 5 2", "4 5 2 3", "5 2 3 4"]);
+        let mut dance = Dance::new(&["2 3 4 5", "3 4 5 2", "4 5 2 3", "5 2 3 4"]).unwrap();
         let expected = [3345, 3245, 3255, 3252, 4252, 4452, 4422, 4423, 2423, 2323];
         (0..10).for_each(|idx| {
             assert_eq!(expected[idx], dance.step(idx % dance.columns.len()));
@@ -103,13 +166,13 @@ mod tests {
 
     #[test]
     fn test_two() {
-        let mut dance = Dance::new(&["2 3 4 5", "6 7 8 9"]);
+        let mut dance = Dance::new(&["2 3 4 5", "6 7 8 9"]).unwrap();
         assert_eq!(50877075, dance.dance_until(2024));
     }
 
     #[test]
     fn test_three() {
-        let mut dance = Dance::new(&["2 3 4 5", "6 7 8 9"]);
+        let dance = Dance::new(&["2 3 4 5", "6 7 8 9"]).unwrap();
         assert_eq!(6584, dance.dance_forever());
     }
 }
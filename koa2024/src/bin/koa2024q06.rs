@@ -1,108 +1,51 @@
-use std::collections::HashMap;
-
-use puzlib::read_lines;
-
-fn main() {
-    let input = parse(&read_lines("koa2024/inputs/quest06.1.txt"));
+use ebclib::{
+    parse::{ParseError, Source},
+    tree::Tree,
+};
+
+#[ebclib::quest(event = "koa", year = 2024, number = 6)]
+fn main() -> Result<(), ParseError> {
+    let input = Source::read("koa2024/inputs/quest06.1.txt")?.parse_with(parse)?;
     println!("Part 1: {}", part_one(&input));
 
-    let input = parse(&read_lines("koa2024/inputs/quest06.2.txt"));
+    let input = Source::read("koa2024/inputs/quest06.2.txt")?.parse_with(parse)?;
     println!("Part 2: {}", part_two(&input));
 
-    let input = parse(&read_lines("koa2024/inputs/quest06.3.txt"));
+    let input = Source::read("koa2024/inputs/quest06.3.txt")?.parse_with(parse)?;
     println!("Part 3: {}", part_three(&input));
+    Ok(())
 }
 
-fn part_one(mapping: &HashMap<String, String>) -> String {
-    get_paths(mapping).join("")
+fn part_one(tree: &Tree) -> String {
+    get_paths(tree).join("")
 }
 
-fn part_two(mapping: &HashMap<String, String>) -> String {
-    get_paths(mapping)
+fn part_two(tree: &Tree) -> String {
+    get_paths(tree)
         .iter()
         .map(|s| s.chars().next().unwrap())
         .collect::<String>()
 }
 
-fn part_three(mapping: &HashMap<String, String>) -> String {
-    get_paths(mapping)
+fn part_three(tree: &Tree) -> String {
+    get_paths(tree)
         .iter()
         .map(|s| s.chars().next().unwrap())
         .collect::<String>()
 }
 
-fn get_paths(mapping: &HashMap<String, String>) -> Vec<String> {
-    let mut paths: HashMap<usize, Vec<Vec<String>>> = HashMap::new();
-    let mut queue = mapping
+fn get_paths(tree: &Tree) -> Vec<String> {
+    let fruit = tree
+        .unique_depth_leaf(|name| name == "@")
+        .expect("No single path to a fruit");
+    tree.path(fruit)
         .iter()
-        .filter_map(|(k, v)| {
-            if k.starts_with("@") {
-                Some((v.as_str(), vec!["@"]))
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<_>>();
-    while let Some((node, steps)) = queue.pop() {
-        let mut new_steps = steps.clone();
-        new_steps.push(node);
-        match mapping[node].as_str() {
-            "RR" => {
-                if steps[0] == "@" {
-                    new_steps.push("RR");
-                    let path = new_steps
-                        .iter()
-                        .rev()
-                        .map(|s| s.to_string())
-                        .collect::<Vec<_>>();
-                    paths
-                        .entry(new_steps.len())
-                        .and_modify(|s| s.push(path.clone()))
-                        .or_insert(vec![path]);
-                }
-            }
-            parent => {
-                if !steps.contains(&parent) {
-                    queue.push((parent, new_steps))
-                }
-            }
-        }
-    }
-    assert_eq!(paths.values().filter(|p| p.len() == 1).count(), 1);
-    paths
-        .values()
-        .filter_map(|p| {
-            if p.len() == 1 {
-                Some(p[0].clone())
-            } else {
-                None
-            }
-        })
-        .next()
-        .unwrap()
+        .map(|node| tree.name(*node).to_string())
+        .collect()
 }
 
-fn parse<S: AsRef<str>>(mapping: &[S]) -> HashMap<String, String> {
-    let mut ends = 0;
-    mapping
-        .iter()
-        .flat_map(|inst| {
-            let (k, v) = inst.as_ref().split_once(':').unwrap();
-            v.split(',')
-                .map(|s| {
-                    (
-                        if s == "@" {
-                            ends += 1;
-                            format!("{s}{ends}")
-                        } else {
-                            s.to_string()
-                        },
-                        k.to_string(),
-                    )
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect()
+fn parse<S: AsRef<str>>(mapping: &[S]) -> Result<Tree, ParseError> {
+    Tree::parse(mapping, "RR")
 }
 
 #[cfg(test)]
@@ -113,7 +56,8 @@ mod tests {
     fn test_one() {
         let mapping = parse(&[
             "RR:A,B,C", "A:D,E", "B:F,@", "C:G,H", "D:@", "E:@", "F:@", "G:@", "H:@",
-        ]);
+        ])
+        .unwrap();
         assert_eq!(vec!["RR", "B", "@"], get_paths(&mapping));
     }
 }
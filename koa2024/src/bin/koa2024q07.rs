@@ -1,68 +1,32 @@
 use num::Integer;
-use std::{
-    collections::{HashMap, HashSet},
-    sync::LazyLock,
-};
+use std::collections::HashSet;
 
-use puzlib::{Dir, Permutations, Vec2D, read_lines};
+use ebclib::{
+    combinatorics::par_sum_permutations,
+    parse::{self, ParseError, Source},
+    track::{Junctions, Track},
+};
 
-fn main() {
-    let plans = read_lines("koa2024/inputs/quest07.1.txt")
-        .iter()
-        .map(|l| {
-            let (name, actions) = l.split_once(':').unwrap();
-            Device::new(name, actions)
-        })
-        .collect::<Vec<Device>>();
+#[ebclib::quest(event = "koa", year = 2024, number = 7)]
+fn main() -> Result<(), ParseError> {
+    let plans = Source::read("koa2024/inputs/quest07.1.txt")?.parse_with(parse_plans)?;
     println!("Part 1: {}", part_one(plans));
 
-    let plans = read_lines("koa2024/inputs/quest07.2.txt")
-        .iter()
-        .map(|l| {
-            let (name, actions) = l.split_once(':').unwrap();
-            Device::new(name, actions)
-        })
-        .collect::<Vec<Device>>();
-    let track = parse_track(
-        "S-=++=-==++=++=-=+=-=+=+=--=-=++=-==++=-+=-=+=-=+=+=++=-+==++=++=-=-=--
--                                                                     -
-=                                                                     =
-+                                                                     +
-=                                                                     +
-+                                                                     =
-=                                                                     =
--                                                                     -
---==++++==+=+++-=+=-=+=-+-=+-=+-=+=-=+=--=+++=++=+++==++==--=+=++==+++-",
-    );
+    let plans = Source::read("koa2024/inputs/quest07.2.txt")?.parse_with(parse_plans)?;
+    let track = Source::read("koa2024/inputs/quest07.2.track.txt")?.parse_with(parse_track)?;
     println!("Part 2: {}", part_two(plans, track));
 
-    let plans = read_lines("koa2024/inputs/quest07.3.txt")
-        .iter()
-        .map(|l| {
-            let (name, actions) = l.split_once(':').unwrap();
-            Device::new(name, actions)
-        })
-        .collect::<Vec<Device>>();
-    let track = parse_track(
-        "S+= +=-== +=++=     =+=+=--=    =-= ++=     +=-  =+=++=-+==+ =++=-=-=--
-- + +   + =   =     =      =   == = - -     - =  =         =-=        -
-= + + +-- =-= ==-==-= --++ +  == == = +     - =  =    ==++=    =++=-=++
-+ + + =     +         =  + + == == ++ =     = =  ==   =   = =++=
-= = + + +== +==     =++ == =+=  =  +  +==-=++ =   =++ --= + =
-+ ==- = + =   = =+= =   =       ++--          +     =   = = =--= ==++==
-=     ==- ==+-- = = = ++= +=--      ==+ ==--= +--+=-= ==- ==   =+=    =
--               = = = =   +  +  ==+ = = +   =        ++    =          -
--               = + + =   +  -  = + = = +   =        +     =          -
---==++++==+=+++-= =-= =-+-=  =+-= =-= =--   +=++=+++==     -=+=++==+++-",
-    );
+    let plans = Source::read("koa2024/inputs/quest07.3.txt")?.parse_with(parse_plans)?;
+    let track = Source::read("koa2024/inputs/quest07.3.track.txt")?.parse_with(parse_track)?;
     println!("Part 3: {}", part_three(plans, track));
+    Ok(())
 }
 
 fn part_one(mut action_plans: Vec<Device>) -> String {
     action_plans
         .iter_mut()
         .for_each(|device| (0..10).for_each(|_| device.enter_segment()));
-    action_plans.sort_by(|a, b| b.score.cmp(&a.score));
+    action_plans.sort_by_key(|device| std::cmp::Reverse(device.score));
     action_plans
         .iter()
         .map(|device| device.name.as_str())
@@ -75,7 +39,7 @@ fn part_two(mut action_plans: Vec<Device>, track: Vec<char>) -> String {
             .iter_mut()
             .for_each(|device| device.lap(&track))
     });
-    action_plans.sort_by(|a, b| b.score.cmp(&a.score));
+    action_plans.sort_by_key(|device| std::cmp::Reverse(device.score));
     action_plans
         .iter()
         .map(|device| device.name.as_str())
@@ -98,18 +62,16 @@ fn part_three(mut action_plans: Vec<Device>, track: Vec<char>) -> usize {
         .unwrap_or(2024);
     (0..laps_to_calculate).for_each(|_| action_plans[0].lap(&track));
     let competitor = action_plans[0].score;
-    let plans = action_plans[0].actions.permutations();
-    plans
-        .map(|p| {
-            let mut d = Device {
-                power: 10,
-                actions: p.clone(),
-                ..Default::default()
-            };
-            (0..laps_to_calculate).for_each(|_| d.lap(&track));
-            if d.score > competitor { 1 } else { 0 }
-        })
-        .sum()
+    // Plans repeat actions a lot, so only try each distinct order once.
+    par_sum_permutations(&action_plans[0].actions, |plan| {
+        let mut d = Device {
+            power: 10,
+            actions: plan.to_vec(),
+            ..Default::default()
+        };
+        (0..laps_to_calculate).for_each(|_| d.lap(&track));
+        if d.score > competitor { 1 } else { 0 }
+    }) as usize
 }
 
 fn factors(mut number: usize) -> HashSet<usize> {
@@ -134,11 +96,11 @@ struct Device {
 }
 
 impl Device {
-    fn new<S: AsRef<str>>(name: S, actions: S) -> Self {
+    fn new(name: &str, actions: Vec<char>) -> Self {
         Self {
-            name: name.as_ref().into(),
+            name: name.into(),
             power: 10,
-            actions: actions.as_ref().chars().filter(|ch| *ch != ',').collect(),
+            actions,
             ..Default::default()
         }
     }
@@ -180,92 +142,47 @@ impl Device {
     }
 }
 
-static DIRS: LazyLock<[Vec2D<i64>; 4]> = LazyLock::new(|| {
-    Dir::<i64>::cardinals(&Vec2D(0, 0))
-        .iter()
-        .map(|d| d.unwrap())
-        .collect::<Vec<_>>()
-        .try_into()
-        .unwrap()
-});
-
-fn parse_track<S: AsRef<str>>(track: S) -> Vec<char> {
-    let lines = track
-        .as_ref()
-        .lines()
-        .enumerate()
-        .flat_map(|(r, l)| {
-            l.chars()
-                .enumerate()
-                .filter_map(|(c, ch)| {
-                    if ch != ' ' {
-                        Some(((r as i64, c as i64), ch))
-                    } else {
-                        None
-                    }
+/// One `NAME:+,-,=` plan of actions per line.
+fn parse_plans<S: AsRef<str>>(lines: &[S]) -> Result<Vec<Device>, ParseError> {
+    parse::lines(lines)
+        .map(|line| {
+            let (name, actions) = line.keyed()?;
+            let actions = actions
+                .iter()
+                .map(|action| match action.parse::<char>() {
+                    Ok(ch @ ('+' | '-' | '=')) => Ok(ch),
+                    _ => Err(action.error("an action +, - or =")),
                 })
-                .collect::<Vec<_>>()
+                .collect::<Result<_, _>>()?;
+            Ok(Device::new(name.as_str(), actions))
         })
-        .collect::<HashMap<(i64, i64), char>>();
-    let mut row = 0;
-    let mut col = 1;
-    let mut offset = 1;
-    let mut track = vec![];
-    loop {
-        match lines.get(&(row, col)) {
-            Some(ch) => {
-                track.push(*ch);
-                if *ch == 'S' {
-                    break;
-                }
-            }
-            None => {
-                row -= DIRS[offset].0;
-                col -= DIRS[offset].1;
-                offset = (offset + 1) % 4;
-                if !lines.contains_key(&(row + DIRS[offset].0, col + DIRS[offset].1)) {
-                    offset = (offset + 2) % 4;
-                }
-            }
-        }
-        row += DIRS[offset].0;
-        col += DIRS[offset].1;
-    }
-    track
+        .collect()
+}
+
+/// Segments of the track in the order they are driven, ending back at the start.
+fn parse_track<S: AsRef<str>>(lines: &[S]) -> Result<Vec<char>, ParseError> {
+    let track = Track::parse(lines, 'S', &['+', '-', '='], Junctions::Reject)?;
+    let mut segments = track.chars().to_vec();
+    segments.rotate_left(1);
+    Ok(segments)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const EXAMPLE: [&str; 4] = ["A:+,-,=,=", "B:+,=,-,+", "C:=,-,+,+", "D:=,=,=,+"];
+
     #[test]
     fn test_one() {
-        let devices = "A:+,-,=,=
-        B:+,=,-,+
-        C:=,-,+,+
-        D:=,=,=,+"
-            .split('\n')
-            .map(|l| {
-                let (name, actions) = l.trim().split_once(':').unwrap();
-                Device::new(name, actions)
-            })
-            .collect::<Vec<Device>>();
+        let devices = parse_plans(&EXAMPLE).unwrap();
         assert_eq!("BDCA", part_one(devices));
     }
 
     #[test]
     fn test_two() {
-        let mut devices = "A:+,-,=,=
-        B:+,=,-,+
-        C:=,-,+,+
-        D:=,=,=,+"
-            .split('\n')
-            .map(|l| {
-                let (name, actions) = l.trim().split_once(':').unwrap();
-                Device::new(name, actions)
-            })
-            .collect::<Vec<Device>>();
-        let track = parse_track("S+===\n-   +\n=+=-+");
+        let mut devices = parse_plans(&EXAMPLE).unwrap();
+        let track = parse_track(&["S+===", "-   +", "=+=-+"]).unwrap();
         devices.iter_mut().for_each(|device| device.lap(&track));
         assert_eq!(
             vec![129, 148, 154, 158],
@@ -282,7 +199,7 @@ mod tests {
     #[test]
     fn test_make_track() {
         assert_eq!(
-            parse_track("S+===\n-   +\n=+=-+"),
+            parse_track(&["S+===", "-   +", "=+=-+"]).unwrap(),
             vec!['+', '=', '=', '=', '+', '+', '-', '=', '+', '=', '-', 'S']
         );
     }
@@ -290,12 +207,7 @@ mod tests {
     #[test]
     fn test_make_bent_track() {
         assert_eq!(
-            parse_track(
-                "S+= ===
-- +++ +
--     +
-=+=-+=="
-            ),
+            parse_track(&["S+= ===", "- +++ +", "-     +", "=+=-+=="]).unwrap(),
             vec![
                 '+', '=', '+', '+', '+', '=', '=', '=', '+', '+', '=', '=', '+', '-', '=', '+',
                 '=', '-', '-', 'S'
@@ -306,7 +218,7 @@ mod tests {
     #[test]
     fn test_make_big_track() {
         let track = parse_track(
-            "S+= +=-== +=++=     =+=+=--=    =-= ++=     +=-  =+=++=-+==+ =++=-=-=--
+            &"S+= +=-== +=++=     =+=+=--=    =-= ++=     +=-  =+=++=-+==+ =++=-=-=--
 - + +   + =   =     =      =   == = - -     - =  =         =-=        -
 = + + +-- =-= ==-==-= --++ +  == == = +     - =  =    ==++=    =++=-=++
 + + + =     +         =  + + == == ++ =     = =  ==   =   = =++=
@@ -315,8 +227,11 @@ mod tests {
 =     ==- ==+-- = = = ++= +=--      ==+ ==--= +--+=-= ==- ==   =+=    =
 -               = = = =   +  +  ==+ = = +   =        ++    =          -
 -               = + + =   +  -  = + = = +   =        +     =          -
---==++++==+=+++-= =-= =-+-=  =+-= =-= =--   +=++=+++==     -=+=++==+++-",
-        );
+--==++++==+=+++-= =-= =-+-=  =+-= =-= =--   +=++=+++==     -=+=++==+++-"
+                .lines()
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
         let expected =
             "+=+++===-+++++=-==+--+=+===-++=====+--===++=-==+=++====-==-===+=+=--==++=+========-==\
 =====++--+++=-++=-+=+==-=++=--+=-====++--+=-==++======+=++=-+==+=-==++=-=-=---++=-=++\
@@ -327,6 +242,16 @@ mod tests {
         assert_eq!(expected, track);
     }
 
+    #[test]
+    fn test_bad_input() {
+        let err = parse_plans(&["A:+,-", "B:+,x,="]).unwrap_err();
+        assert_eq!("an action +, - or =", err.expected);
+        assert_eq!((2, 5), err.location.map(|at| (at.line, at.column)).unwrap());
+        let err = parse_track(&["S+=+", "- ==", "=+=-"]).unwrap_err();
+        assert_eq!("one way on, not a branch", err.expected);
+        assert_eq!((1, 3), err.location.map(|at| (at.line, at.column)).unwrap());
+    }
+
     #[test]
     fn test_factors() {
         assert_eq!(HashSet::from([3, 5]), factors(15));
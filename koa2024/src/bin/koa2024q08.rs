@@ -1,44 +1,43 @@
 use std::collections::VecDeque;
 
-use puzlib::read_lines;
+use ebclib::{
+    modint::ModInt,
+    schema::{self, Report},
+};
 
-fn main() {
-    let blocks = read_lines("koa2024/inputs/quest08.1.txt")[0]
-        .parse::<i64>()
-        .unwrap();
+#[ebclib::quest(event = "koa", year = 2024, number = 8)]
+fn main() -> Result<(), Report> {
+    let blocks = schema::load_input("koa2024/inputs/quest08.1.txt")?;
     println!("Part 1: {}", part_one(blocks));
 
-    let priests = read_lines("koa2024/inputs/quest08.2.txt")[0]
-        .parse::<i64>()
-        .unwrap();
+    let priests = schema::load_input("koa2024/inputs/quest08.2.txt")?;
     println!("Part 2: {}", part_two(priests, 1111, 20240000));
 
-    let priests = read_lines("koa2024/inputs/quest08.3.txt")[0]
-        .parse::<u64>()
-        .unwrap();
+    let priests = schema::load_input("koa2024/inputs/quest08.3.txt")?;
     println!("Part 3: {}", part_three(priests, 10, 202400000));
+    Ok(())
 }
 
-fn part_one(available: i64) -> i64 {
-    let mut next_biggest = 0_i64;
+fn part_one(available: u64) -> u64 {
+    // No blocks is an empty pyramid, already complete like any square number.
+    if available == 0 {
+        return 0;
+    }
+    let mut next_biggest = 0_u64;
     while next_biggest.pow(2) < available {
         next_biggest += 1;
     }
     (next_biggest.pow(2) - available) * (2 * next_biggest - 1)
 }
 
-fn part_two(priests: i64, acolytes: i64, blocks: i64) -> i64 {
+fn part_two(priests: u64, acolytes: u64, blocks: u64) -> u64 {
     let mut width = 1;
-    let mut thickness = 1;
+    let mut thickness = ModInt::new(1, acolytes);
     let mut total = 1;
     while total < blocks {
-        // Assuming multiplications will become large, we're distributing the modulo.
-        // (a * b) % n == ((a % n) * (b % n)) %n
-        thickness = ((thickness % acolytes) * (priests % acolytes)) % acolytes;
+        thickness *= priests;
         width += 2;
-        total += width * thickness;
-        #[cfg(test)]
-        println!("Adding {thickness} * {width}. Total needed {total}");
+        total += width * thickness.value();
     }
     (total - blocks) * width
 }
@@ -48,13 +47,9 @@ fn part_three(priests: u64, acolytes: u64, blocks: u64) -> u64 {
     let mut width = 1;
     let mut thickness = 1;
     heights.push_front(1);
-    #[cfg(test)]
-    println!("{heights:?}");
     let mut total = 1;
     loop {
-        // Assuming multiplications will become large, we're distributing the modulo.
-        // (a * b) % n == ((a % n) * (b % n)) %n
-        thickness = ((thickness % acolytes) * (priests % acolytes)) % acolytes + acolytes;
+        thickness = (ModInt::new(thickness, acolytes) * priests).value() + acolytes;
         width += 2;
         heights.iter_mut().for_each(|h| *h += thickness);
         heights.push_front(thickness);
@@ -64,7 +59,7 @@ fn part_three(priests: u64, acolytes: u64, blocks: u64) -> u64 {
             .iter()
             .skip(1)
             .take(heights.len() - 2)
-            .map(|&h| (priests % acolytes * width % acolytes * h % acolytes) % acolytes)
+            .map(|&h| (ModInt::new(priests, acolytes) * width * h).value())
             .collect::<Vec<_>>();
         let removed = removed.iter().sum::<u64>();
         if (total - removed) > blocks {
@@ -82,6 +77,8 @@ mod tests {
         let expected = 21;
         let actual = part_one(13);
         assert_eq!(expected, actual);
+        assert_eq!(0, part_one(0));
+        assert_eq!(0, part_one(9));
     }
 
     #[test]
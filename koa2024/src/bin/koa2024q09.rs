@@ -1,56 +1,54 @@
-use puzlib::read_numbers;
+use ebclib::{
+    dp::coin_change::CoinChange,
+    schema::{self, Report},
+};
 
-fn main() {
-    let input = read_numbers("koa2024/inputs/quest09.1.txt");
+#[ebclib::quest(event = "koa", year = 2024, number = 9)]
+fn main() -> Result<(), Report> {
+    let input: Vec<usize> = schema::load_input("koa2024/inputs/quest09.1.txt")?;
     println!("Part 1: {}", part_one(&input));
 
-    let input = read_numbers("koa2024/inputs/quest09.2.txt");
+    let input: Vec<usize> = schema::load_input("koa2024/inputs/quest09.2.txt")?;
     println!("Part 2: {}", part_two(&input));
 
-    let input = read_numbers("koa2024/inputs/quest09.3.txt");
+    let input: Vec<usize> = schema::load_input("koa2024/inputs/quest09.3.txt")?;
     println!("Part 3: {}", part_three(&input));
+    Ok(())
 }
 
-fn part_one(sparkballs: &[usize]) -> usize {
+fn part_one(sparkballs: &[usize]) -> u64 {
     let stamps = [1, 3, 5, 10];
     let values = get_beetle_count(sparkballs, &stamps);
-    sparkballs.iter().map(|s| values[*s]).sum()
+    sparkballs
+        .iter()
+        .map(|s| values.min_coins(*s as u64).unwrap())
+        .sum()
 }
 
-fn part_two(sparkballs: &[usize]) -> usize {
+fn part_two(sparkballs: &[usize]) -> u64 {
     let stamps = [1, 3, 5, 10, 15, 16, 20, 24, 25, 30];
     let values = get_beetle_count(sparkballs, &stamps);
-    sparkballs.iter().map(|s| values[*s]).sum()
+    sparkballs
+        .iter()
+        .map(|s| values.min_coins(*s as u64).unwrap())
+        .sum()
 }
 
-fn part_three(sparkballs: &[usize]) -> usize {
+fn part_three(sparkballs: &[usize]) -> u64 {
     let stamps = [
         1, 3, 5, 10, 15, 16, 20, 24, 25, 30, 37, 38, 49, 50, 74, 75, 100, 101,
     ];
     let values = get_beetle_count(sparkballs, &stamps);
+    // Two piles of beetles that differ by no more than 100.
     sparkballs
         .iter()
-        .map(|sparkball| {
-            (0..=50)
-                .map(|delta| {
-                    let left = sparkball / 2 + delta;
-                    let right = sparkball - left;
-                    values[left] + values[right]
-                })
-                .min()
-                .unwrap()
-        })
+        .map(|sparkball| values.split(*sparkball as u64, 2, 100).unwrap())
         .sum()
 }
 
-fn get_beetle_count(sparkballs: &[usize], stamps: &[usize]) -> Vec<usize> {
-    let biggest = *sparkballs.iter().max().unwrap() + 1;
-    let mut counts = vec![biggest; biggest];
-    counts[0] = 0;
-    stamps.iter().for_each(|stamp| {
-        (*stamp..biggest).for_each(|idx| counts[idx] = counts[idx].min(counts[idx - *stamp] + 1))
-    });
-    counts
+fn get_beetle_count(sparkballs: &[usize], stamps: &[u64]) -> CoinChange {
+    let biggest = *sparkballs.iter().max().unwrap() as u64;
+    CoinChange::new(stamps, biggest)
 }
 
 #[cfg(test)]
@@ -59,7 +57,10 @@ mod tests {
 
     #[test]
     fn test_simple_count() {
-        assert_eq!(3, get_beetle_count(&[19], &[1, 5, 6, 9])[19]);
+        assert_eq!(
+            Some(3),
+            get_beetle_count(&[19], &[1, 5, 6, 9]).min_coins(19)
+        );
     }
 
     #[test]
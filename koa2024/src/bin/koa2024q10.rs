@@ -2,6 +2,7 @@ use std::collections::HashSet;
 
 use puzlib::read_grid;
 
+#[ebclib::quest(event = "koa", year = 2024, number = 10)]
 fn main() {
     let grid = read_grid("koa2024/inputs/quest10.1.txt");
     println!("Part 1: {}", part_one(grid));
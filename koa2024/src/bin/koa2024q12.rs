@@ -1,29 +1,30 @@
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
 
-use puzlib::read_lines;
+use ebclib::{
+    ballistics::{self, Meteor},
+    par::par_sum,
+    parse::{self, ParseError, Source},
+};
 
-fn main() {
-    let input = read_lines("koa2024/inputs/quest12.1.txt");
-    println!("Part 1: {}", stationary(parse_input(input)));
+#[ebclib::quest(event = "koa", year = 2024, number = 12)]
+fn main() -> Result<(), ParseError> {
+    let input = Source::read("koa2024/inputs/quest12.1.txt")?;
+    println!("Part 1: {}", stationary(input.parse_with(parse_input)?));
 
-    let input = read_lines("koa2024/inputs/quest12.2.txt");
-    println!("Part 2: {}", stationary(parse_input(input)));
+    let input = Source::read("koa2024/inputs/quest12.2.txt")?;
+    println!("Part 2: {}", stationary(input.parse_with(parse_input)?));
 
-    let input = read_lines("koa2024/inputs/quest12.3.txt")
-        .iter()
-        .filter_map(|line| {
-            line.split_once(' ')
-                .map(|v| (v.0.parse().unwrap(), v.1.parse().unwrap()))
-        })
-        .collect();
-    println!("Part 3: {}", moving(input));
+    let input = Source::read("koa2024/inputs/quest12.3.txt")?;
+    println!("Part 3: {}", moving(input.parse_with(parse_meteors)?));
+    Ok(())
 }
 
-fn parse_input(lines: Vec<String>) -> Vec<(usize, usize, usize)> {
+fn parse_input<S: AsRef<str>>(lines: &[S]) -> Result<Vec<(usize, usize, usize)>, ParseError> {
     let mut offset = 0;
     let mut res = Vec::new();
+    let lines = parse::lines(lines).collect::<Vec<_>>();
     for (row, line) in lines.iter().rev().enumerate().skip(1) {
-        for (col, ch) in line.chars().enumerate() {
+        for (col, ch) in line.as_str().chars().enumerate() {
             match ch {
                 'A' => offset = col,
                 'T' => res.push((col - offset, row - 1, 1)),
@@ -38,18 +39,18 @@ fn parse_input(lines: Vec<String>) -> Vec<(usize, usize, usize)> {
         Ordering::Equal => a.0.cmp(&b.0),
         o => o,
     });
-    res
+    Ok(res)
+}
+
+/// One `x y` meteor position per line.
+fn parse_meteors<S: AsRef<str>>(lines: &[S]) -> Result<Vec<(usize, usize)>, ParseError> {
+    parse::lines(lines).map(|line| line.tuple()).collect()
 }
 
 fn score_target(catapult: usize, target: &(usize, usize, usize)) -> Option<usize> {
     let (x, y, hp) = target;
-    let a = x + y - catapult;
-    if a.is_multiple_of(3) {
-        let power = a / 3;
-        Some(hp * (catapult + 1) * power)
-    } else {
-        None
-    }
+    let power = ballistics::lowest_power(catapult as i64, (*x as i64, *y as i64))?;
+    Some(hp * (catapult + 1) * power as usize)
 }
 
 /*
@@ -72,37 +73,16 @@ B(P5) hits at H=3 takes 19
 C(P4) hits at H=2 takes 15
 */
 
-fn moving_target(catapult: usize, start: &(usize, usize)) -> Option<usize> {
-    let &(mut x0, mut y0) = start;
-    // Wait 1 time unit if X is odd. Otherwise they won't hit in discrete time.
-    if x0 & 1 == 1 {
-        x0 -= 1;
-        y0 -= 1;
-    };
-
-    // Max height is at a distance of half of x.
-    let x1 = x0 / 2;
-    let y1 = y0 - x1;
-    let h = catapult + 1;
-
-    // In line with a catapult. Hit on the way up.
-    if x0 + catapult == y0 {
-        return Some(h * x1);
-    }
-
-    // Hit in the horizontal section
-    if let Some(power) = y1.checked_sub(catapult)
-        && (power..=2 * power).contains(&x1)
-    {
-        return Some(h * power);
-    }
-
-    // Hit on the way back down.
-    if y1 <= x1 + catapult {
-        score_target(catapult, &(x1, y1, 1))
-    } else {
-        None
-    }
+/// Ranking of the shot that hits the meteor highest, the lowest ranking on a tie.
+fn moving_target(start: &(usize, usize)) -> Option<usize> {
+    let meteor = Meteor::falling(start.0 as i64, start.1 as i64);
+    (0..3)
+        .filter_map(|catapult| {
+            let (_, power, hit) = ballistics::earliest_hit(catapult, &meteor)?;
+            Some((Reverse(hit.y), (catapult + 1) as usize * power as usize))
+        })
+        .min()
+        .map(|(_, ranking)| ranking)
 }
 
 fn stationary(targets: Vec<(usize, usize, usize)>) -> usize {
@@ -113,15 +93,7 @@ fn stationary(targets: Vec<(usize, usize, usize)>) -> usize {
 }
 
 fn moving(positions: Vec<(usize, usize)>) -> usize {
-    positions
-        .iter()
-        .map(|t| {
-            (0..3)
-                .filter_map(|c| moving_target(c, t))
-                .min()
-                .unwrap_or(0)
-        })
-        .sum()
+    par_sum(&positions, |t| moving_target(t).unwrap_or(0))
 }
 
 #[cfg(test)]
@@ -137,9 +109,8 @@ mod tests {
 .A......T.T..
 ============="#
             .lines()
-            .map(|l| l.into())
-            .collect();
-        let actual = parse_input(lines);
+            .collect::<Vec<_>>();
+        let actual = parse_input(&lines).unwrap();
         assert_eq!(expected, actual);
     }
 
@@ -151,9 +122,8 @@ mod tests {
 .A......T.T..
 ============="#
             .lines()
-            .map(|l| l.into())
-            .collect();
-        let targets = parse_input(lines);
+            .collect::<Vec<_>>();
+        let targets = parse_input(&lines).unwrap();
         let actual = stationary(targets);
         let expected = 13;
         assert_eq!(expected, actual);
@@ -162,7 +132,7 @@ mod tests {
     #[test]
     fn test_test_moving() {
         let expected = Some(2);
-        let actual = (0..3).filter_map(|c| moving_target(c, &(6, 5))).min();
+        let actual = moving_target(&(6, 5));
         assert_eq!(expected, actual);
     }
 
@@ -1,7 +1,10 @@
-use std::collections::HashMap;
-
-use puzlib::{Dir, Graph, Vec2D, Weighted, dijkstra, read_lines};
+use ebclib::grid::{
+    Grid,
+    weighted::{GridGraph, cost},
+};
+use puzlib::read_lines;
 
+#[ebclib::quest(event = "koa", year = 2024, number = 13)]
 fn main() {
     let input = read_lines("koa2024/inputs/quest13.1.txt");
     let chamber: Chamber = input.into();
@@ -12,111 +15,34 @@ fn main() {
     println!("Part 2: {}", chamber.traverse());
 
     let input = read_lines("koa2024/inputs/quest13.3.txt");
-    let mut chamber: Chamber = input.into();
-    chamber.from_end = true;
+    let chamber: Chamber = input.into();
     println!("Part 3: {}", chamber.traverse());
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct Chamber {
-    chamber: HashMap<Vec2D<i64>, i64>,
-    start: Vec<Vec2D<i64>>,
-    end: Vec2D<i64>,
-    size: (usize, usize),
-    from_end: bool,
+    grid: Grid<char>,
 }
 
 impl Chamber {
-    fn traverse(&self) -> usize {
-        let mut res = usize::MAX;
-        if self.from_end {
-            if let Some((m, p)) = dijkstra(&self.end, self) {
-                let found = p[p.len() - 1];
-                res = m[&found]
-            };
-        } else {
-            for start in self.start.iter() {
-                if let Some((m, _)) = dijkstra(start, self) {
-                    res = res.min(m[&self.end]);
-                };
-            }
-        }
-        res
-    }
-}
-
-impl Graph for Chamber {
-    type Node = Vec2D<i64>;
-
-    fn height(&self) -> usize {
-        self.size.0
-    }
-
-    fn width(&self) -> usize {
-        self.size.1
-    }
-
-    fn moves(&self, node: &Self::Node) -> Vec<Self::Node> {
-        Dir::<i64>::cardinals(node)
-            .iter()
-            .filter_map(|n| {
-                if let Some(next) = n
-                    && self.chamber.contains_key(next)
-                {
-                    Some(*next)
-                } else {
-                    None
-                }
-            })
-            .collect()
-    }
-
-    fn is_done(&self, node: &Self::Node) -> bool {
-        if self.from_end {
-            self.start.contains(node)
-        } else {
-            node == &self.end
-        }
-    }
-}
-impl Weighted for Chamber {
-    fn weight(&self, cur: &Self::Node, next: &Self::Node) -> usize {
-        let a = self.chamber[cur];
-        let b = self.chamber[next];
-        let cost = (a - b).abs().min(10 - (b - a).abs()) + 1;
-        cost as usize
+    /// Shortest time from any start to the end, with levels on a 0-9 dial.
+    fn traverse(&self) -> u64 {
+        let graph = GridGraph::new(&self.grid)
+            .passable(|c| matches!(c, 'S' | 'E' | '0'..='9'))
+            .cost(cost::cyclic_level(1, 10, |c: &char| {
+                c.to_digit(10).unwrap_or(0) as i64
+            }));
+        let starts = self.grid.positions(|c| *c == 'S');
+        let end = self.grid.positions(|c| *c == 'E');
+        graph.nearest(starts, end).unwrap().1
     }
 }
 
 impl<S: AsRef<str>> From<Vec<S>> for Chamber {
     fn from(value: Vec<S>) -> Self {
-        let mut chamber = Chamber {
-            size: (value.len(), value[0].as_ref().len()),
-            ..Default::default()
-        };
-        for (row, line) in value.into_iter().enumerate() {
-            for (col, ch) in line.as_ref().chars().enumerate() {
-                let row = row as i64;
-                let col = col as i64;
-                match ch {
-                    'E' => {
-                        chamber.end = Vec2D(row, col);
-                        chamber.chamber.insert(Vec2D(row, col), 0);
-                    }
-                    'S' => {
-                        chamber.start.push(Vec2D(row, col));
-                        chamber.chamber.insert(Vec2D(row, col), 0);
-                    }
-                    x if x.is_ascii_digit() => {
-                        chamber
-                            .chamber
-                            .insert(Vec2D(row, col), (x as u8 - b'0') as i64);
-                    }
-                    _ => (),
-                }
-            }
+        Chamber {
+            grid: Grid::parse(&value),
         }
-        chamber
     }
 }
 
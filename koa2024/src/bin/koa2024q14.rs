@@ -1,153 +1,56 @@
-use std::{
-    collections::{HashMap, HashSet, VecDeque},
-    str::FromStr,
+use ebclib::{
+    parse::{self, ParseError, Source},
+    voxel::{Step, VoxelSet, parse_path, walk},
 };
 
-use puzlib::{Vec3D, get_path, read_lines};
+#[ebclib::quest(event = "koa", year = 2024, number = 14)]
+fn main() -> Result<(), ParseError> {
+    let mut input = Source::read("koa2024/inputs/quest14.1.txt")?.parse_with(parse_input)?;
+    let path = input.pop().ok_or_else(|| ParseError::new("a path"))?;
+    println!("Part 1: {}", part_one(path));
 
-fn main() {
-    let input = read_lines("koa2024/inputs/quest14.1.txt");
-    println!("Part 1: {}", part_one(parse_input(input).pop().unwrap()));
+    let input = Source::read("koa2024/inputs/quest14.2.txt")?.parse_with(parse_input)?;
+    println!("Part 2: {}", part_two(input));
 
-    let input = read_lines("koa2024/inputs/quest14.2.txt");
-    println!("Part 2: {}", part_two(parse_input(input)));
-
-    let input = read_lines("koa2024/inputs/quest14.3.txt");
-    println!("Part 3: {}", part_three(parse_input(input)));
+    let input = Source::read("koa2024/inputs/quest14.3.txt")?.parse_with(parse_input)?;
+    println!("Part 3: {}", part_three(input));
+    Ok(())
 }
 
-fn parse_input(input: Vec<String>) -> Vec<Vec<Instruction>> {
-    input
-        .iter()
-        .map(|l| {
-            l.split(',')
-                .map(|i| i.parse().unwrap())
-                .collect::<Vec<Instruction>>()
-        })
+/// One comma separated path per line.
+fn parse_input<S: AsRef<str>>(input: &[S]) -> Result<Vec<Vec<Step>>, ParseError> {
+    parse::lines(input)
+        .map(|line| parse_path(line.trim()))
         .collect()
 }
 
-fn part_one(instructions: Vec<Instruction>) -> i64 {
+fn part_one(steps: Vec<Step>) -> i64 {
     let mut max_height = 0;
-    let mut cur = Vec3D(0, 0, 0);
-    for inst in instructions {
-        cur += inst.as_vec3d();
-        max_height = max_height.max(cur.2);
+    let mut cur = [0, 0, 0];
+    for step in steps {
+        cur = walk(cur, &[step]);
+        max_height = max_height.max(cur[2]);
     }
     max_height
 }
 
-fn part_two(instructions: Vec<Vec<Instruction>>) -> usize {
-    let mut visited = HashSet::new();
-    for branch in instructions {
-        let mut cur = Vec3D(0, 0, 0);
-        for inst in branch {
-            for _ in 0..inst.scale {
-                cur += inst.direction;
-                visited.insert(cur);
-            }
-        }
-    }
-    visited.len()
-}
-
-fn part_three(instructions: Vec<Vec<Instruction>>) -> i64 {
-    let mut leaves = vec![];
-    let mut visited = HashSet::new();
-    let mut height = 0;
-    for branch in instructions {
-        let mut cur = Vec3D(0, 0, 0);
-        for inst in branch {
-            for _ in 0..inst.scale {
-                cur += inst.direction;
-                height = height.max(cur.2);
-                visited.insert(cur);
-            }
-        }
-        leaves.push(cur);
+fn part_two(branches: Vec<Vec<Step>>) -> usize {
+    let mut tree = VoxelSet::new();
+    for branch in branches {
+        tree.trace([0, 0, 0], &branch);
     }
-    let mut best = i64::MAX;
-    for z in 1..=height {
-        if let Some(found) = leaves
-            .iter()
-            .map(|l| bfs(Vec3D(0, 0, z), l, &visited))
-            .sum()
-        {
-            best = best.min(found)
-        }
-    }
-    best
+    tree.len()
 }
 
-fn bfs(start: Vec3D<i64>, end: &Vec3D<i64>, graph: &HashSet<Vec3D<i64>>) -> Option<i64> {
-    let mut path = HashMap::new();
-    let mut to_visit = VecDeque::new();
-    to_visit.push_front(start);
-    while let Some(node) = to_visit.pop_front() {
-        if node == *end {
-            let path = get_path(path, node, &start);
-            return Some(path.len() as i64 - 1);
-        }
-        for next_move in moves(&node, graph) {
-            if path.contains_key(&next_move) {
-                continue;
-            }
-            to_visit.push_back(next_move);
-            path.insert(next_move, node);
-        }
-    }
-    None
-}
-
-fn moves(node: &Vec3D<i64>, graph: &HashSet<Vec3D<i64>>) -> Vec<Vec3D<i64>> {
-    [
-        Vec3D(0, 0, 1),
-        Vec3D(0, 0, -1),
-        Vec3D(0, 1, 0),
-        Vec3D(0, -1, 0),
-        Vec3D(1, 0, 0),
-        Vec3D(-1, 0, 0),
-    ]
-    .iter()
-    .filter_map(|n| {
-        let next = node + n;
-        if graph.contains(&next) {
-            Some(next)
-        } else {
-            None
-        }
-    })
-    .collect()
-}
-
-#[derive(Debug, Default)]
-struct Instruction {
-    direction: Vec3D<i64>,
-    scale: i64,
-}
-
-impl Instruction {
-    fn as_vec3d(&self) -> Vec3D<i64> {
-        self.direction.scale(self.scale)
-    }
-}
-
-impl FromStr for Instruction {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let direction = match s[..1].as_ref() {
-            "U" => Vec3D(0, 0, 1),
-            "D" => Vec3D(0, 0, -1),
-            "L" => Vec3D(0, 1, 0),
-            "R" => Vec3D(0, -1, 0),
-            "F" => Vec3D(1, 0, 0),
-            "B" => Vec3D(-1, 0, 0),
-            c => return Err(format!("Unknown direction {c}")),
-        };
-        let scale = s[1..].parse().unwrap();
-        Ok(Self { direction, scale })
-    }
+fn part_three(branches: Vec<Vec<Step>>) -> usize {
+    let mut tree = VoxelSet::new();
+    let leaves = branches
+        .iter()
+        .map(|branch| tree.trace([0, 0, 0], branch))
+        .collect::<Vec<_>>();
+    let height = tree.bounds().unwrap().max[2];
+    let trunk = (1..=height).map(|z| [0, 0, z]);
+    tree.best_meeting_point(&leaves, trunk).unwrap().1
 }
 
 #[cfg(test)]
@@ -160,7 +63,7 @@ mod tests {
         let instructions = "U5,R3,D2,L5,U4,R5,D2"
             .split(',')
             .map(|s| s.parse().unwrap())
-            .collect::<Vec<Instruction>>();
+            .collect::<Vec<Step>>();
         let actual = part_one(instructions);
         assert_eq!(expected, actual);
     }
@@ -173,7 +76,7 @@ mod tests {
             .map(|l| {
                 l.split(',')
                     .map(|s| s.parse().unwrap())
-                    .collect::<Vec<Instruction>>()
+                    .collect::<Vec<Step>>()
             })
             .collect::<Vec<_>>();
         println!("{instructions:?}");
@@ -189,7 +92,7 @@ mod tests {
             .map(|l| {
                 l.split(',')
                     .map(|s| s.parse().unwrap())
-                    .collect::<Vec<Instruction>>()
+                    .collect::<Vec<Step>>()
             })
             .collect::<Vec<_>>();
         let actual = part_three(instructions);
@@ -1,7 +1,12 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::HashMap;
 
+use ebclib::{
+    bitset::{Alphabet, SymbolSet},
+    search::Search,
+};
 use puzlib::{Dir, Vec2D, read_grid_to_map};
 
+#[ebclib::quest(event = "koa", year = 2024, number = 15)]
 fn main() {
     let input = read_grid_to_map("koa2024/inputs/quest15.1.txt");
     let garden: Garden = input.into();
@@ -19,60 +24,39 @@ fn main() {
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
 struct State {
     node: Vec2D<i64>,
-    collected: u32,
-    steps: usize,
+    collected: SymbolSet,
 }
 
 #[derive(Debug, Default)]
 struct Garden {
-    map: HashMap<Vec2D<i64>, u32>,
+    map: HashMap<Vec2D<i64>, SymbolSet>,
     start: Vec2D<i64>,
-    herb_mask: u32,
+    herbs: SymbolSet,
 }
 
 impl Garden {
     fn find_path_to_herbs(&self) -> usize {
-        let mut visited = HashSet::new();
         let mut most_herbs = 0;
-        let mut to_visit = VecDeque::new();
-        to_visit.push_back(State {
+        let mut search = Search::new(|state: &State| {
+            let held = state.collected;
+            self.neighbors(&state.node).into_iter().map(move |node| {
+                let collected = held | self.map[&node];
+                (State { node, collected }, 1)
+            })
+        })
+        .goal(|state| state.node == self.start && state.collected == self.herbs)
+        // Prune paths that have collected 2 fewer herbs than the best path expanded so far.
+        .prune(move |state, _| {
+            let herbs = state.collected.len();
+            most_herbs = most_herbs.max(herbs);
+            herbs + 2 < most_herbs
+        })
+        .prune_on_pop();
+        let start = State {
             node: self.start,
             collected: self.map[&self.start],
-            steps: 0,
-        });
-        while let Some(State {
-            node,
-            collected,
-            steps,
-        }) = to_visit.pop_front()
-        {
-            if node == self.start && collected == self.herb_mask {
-                return steps;
-            }
-            let coll = if self.map[&node] != self.map[&self.start] {
-                collected | self.map[&node]
-            } else {
-                collected
-            };
-            let herbs = u32::count_ones(coll);
-            // Prune paths that have collected 2 fewer herbs than the best path.
-            if herbs + 2 < most_herbs {
-                continue;
-            }
-            most_herbs = most_herbs.max(herbs);
-            for next in self.neighbors(&node) {
-                let mut next_state = State {
-                    node: next,
-                    collected: coll,
-                    steps: 0,
-                };
-                if visited.insert(next_state) {
-                    next_state.steps = steps + 1;
-                    to_visit.push_back(next_state);
-                }
-            }
-        }
-        0
+        };
+        search.bfs([start]).map_or(0, |found| found.cost)
     }
 
     fn neighbors(&self, node: &Vec2D<i64>) -> Vec<Vec2D<i64>> {
@@ -94,28 +78,23 @@ impl Garden {
 impl From<Vec<((usize, usize), char)>> for Garden {
     fn from(value: Vec<((usize, usize), char)>) -> Self {
         let mut garden = Self::default();
+        let mut herbs = Alphabet::new();
         for ((x, y), char) in value {
-            let x = x as i64;
-            let y = y as i64;
+            let node = Vec2D(x as i64, y as i64);
             match char {
                 '.' => {
                     if x == 0 {
-                        let v = 1 << (b'S' - b'A');
-                        garden.map.insert(Vec2D(x, y), v);
-                        garden.herb_mask |= v;
-                        garden.start = Vec2D(x, y);
-                    } else {
-                        garden.map.insert(Vec2D(x, y), 0);
+                        garden.start = node;
                     }
+                    garden.map.insert(node, SymbolSet::new());
                 }
                 '#' | '~' => (),
                 c => {
-                    let v = 1 << (c as u8 - b'A');
-                    garden.map.insert(Vec2D(x, y), v);
-                    garden.herb_mask |= v;
+                    garden.map.insert(node, herbs.set([c]));
                 }
             }
         }
+        garden.herbs = herbs.full();
         garden
     }
 }
@@ -1,76 +1,60 @@
 use std::collections::HashMap;
 
-use puzlib::{lcm, read_lines};
-
-fn main() {
-    let input = read_lines("koa2024/inputs/quest16.1.txt");
-    let wheels = parse_input(input);
+use ebclib::{
+    cycle,
+    dp::lattice::Lattice,
+    parse::{self, ParseError, Source},
+    ring::Ring,
+};
+
+#[ebclib::quest(event = "koa", year = 2024, number = 16)]
+fn main() -> Result<(), ParseError> {
+    let wheels = Source::read("koa2024/inputs/quest16.1.txt")?.parse_with(parse_input)?;
     println!("Part 1: {}", part_one(wheels, 100));
 
-    let input = read_lines("koa2024/inputs/quest16.2.txt");
-    let wheels = parse_input(input);
+    let wheels = Source::read("koa2024/inputs/quest16.2.txt")?.parse_with(parse_input)?;
     println!("Part 2: {}", part_two(wheels, 202420242024));
 
-    let input = read_lines("koa2024/inputs/quest16.3.txt");
-    let wheels = parse_input(input);
+    let wheels = Source::read("koa2024/inputs/quest16.3.txt")?.parse_with(parse_input)?;
     println!("Part 3: {}", part_three(wheels, 256));
+    Ok(())
 }
 
 fn part_one(wheels: Vec<Wheel>, pulls: usize) -> String {
     let wh = wheels
         .iter()
-        .map(|wheel| wheel.position(pulls, 0, 0).iter().collect::<String>())
+        .map(|wheel| wheel.position(pulls, 0).iter().collect::<String>())
         .collect::<Vec<_>>();
     wh.join(" ")
 }
 
 fn part_two(wheels: Vec<Wheel>, pulls: usize) -> usize {
-    let cycle = wheels
-        .iter()
-        .fold(1, |acc, wheel| lcm(acc, wheel.sequence.len()));
-
-    let mut coins = 0;
-    for pull in 1..=cycle {
-        coins += score(wheels.iter().map(|wheel| wheel.position(pull, 0, 0)));
-    }
-    coins *= pulls / cycle;
-    let rem = pulls % cycle;
-    for pull in 0..rem {
-        coins += score(wheels.iter().map(|wheel| wheel.position(pull, 0, 0)));
-    }
-
-    coins
+    // Track where each wheel is so the repeat can be found without working out the period.
+    let start = vec![0; wheels.len()];
+    let pull = |positions: &Vec<i64>| {
+        wheels
+            .iter()
+            .zip(positions)
+            .map(|(wheel, pos)| (pos + wheel.steps).rem_euclid(wheel.sequence.len() as i64))
+            .collect::<Vec<_>>()
+    };
+    let coins = |positions: &Vec<i64>| {
+        score(
+            wheels
+                .iter()
+                .zip(positions)
+                .map(|(wheel, pos)| wheel.sequence[*pos]),
+        ) as u64
+    };
+    cycle::sum_over(&start, pull, coins, pulls as u64) as usize
 }
 
 fn part_three(wheels: Vec<Wheel>, pulls: usize) -> String {
-    let mid = pulls + 1;
-    let mut cur_min = vec![usize::MAX; 2 * mid + 1];
-    let mut next_min = vec![usize::MAX; 2 * mid + 1];
-    let mut cur_max = vec![0; 2 * mid + 1];
-    let mut next_max = vec![0; 2 * mid + 1];
-    cur_min[mid] = 0;
-
-    for pull in 1..=pulls {
-        for value in (mid - pull)..=(mid + pull) {
-            let backward = mid.saturating_sub(value);
-            let foreward = value.saturating_sub(mid);
-            let score = score(
-                wheels
-                    .iter()
-                    .map(|wheel| wheel.position(pull, backward, foreward)),
-            );
-            next_min[value] = score + cur_min[value - 1..=value + 1].iter().min().unwrap();
-            next_max[value] = score + cur_max[value - 1..=value + 1].iter().max().unwrap();
-        }
-        (cur_min, next_min) = (next_min, cur_min);
-        (cur_max, next_max) = (next_max, cur_max);
-    }
-
-    format!(
-        "{} {}",
-        cur_max.iter().max().unwrap(),
-        cur_min.iter().min().unwrap()
-    )
+    // Each pull the lever can also be nudged one step back or forward.
+    let lattice = Lattice::new(&[-1, 0, 1], pulls);
+    let (min, max) = lattice
+        .min_max(|pull, offset| score(wheels.iter().map(|wheel| wheel.position(pull, offset))));
+    format!("{max} {min}")
 }
 
 fn score<T: Iterator<Item = [char; 3]>>(wheels: T) -> usize {
@@ -86,36 +70,44 @@ fn score<T: Iterator<Item = [char; 3]>>(wheels: T) -> usize {
 
 #[derive(Debug, Default)]
 struct Wheel {
-    steps: usize,
-    sequence: Vec<[char; 3]>,
+    steps: i64,
+    sequence: Ring<[char; 3]>,
 }
 
 impl Wheel {
-    fn position(&self, pull: usize, backward: usize, foreward: usize) -> [char; 3] {
-        let ct = self.sequence.len();
-        self.sequence[(self.steps * pull + foreward + (ct - backward % ct)) % ct]
+    /// Face showing after `pull` pulls with the lever nudged `offset` steps in total.
+    fn position(&self, pull: usize, offset: i64) -> [char; 3] {
+        *self.sequence.after(offset, self.steps, pull as i64)
     }
 }
 
-fn parse_input(input: Vec<String>) -> Vec<Wheel> {
-    let mut wheels = input[0]
-        .split(',')
+/// Steps for each wheel, then rows of 3 character faces in columns, one per wheel.
+fn parse_input<S: AsRef<str>>(input: &[S]) -> Result<Vec<Wheel>, ParseError> {
+    let (header, rows) = parse::headed(input)?;
+    let mut wheels = header
+        .list(',')?
+        .into_iter()
         .map(|steps| Wheel {
-            steps: steps.parse().unwrap(),
+            steps,
             ..Default::default()
         })
         .collect::<Vec<_>>();
-    for row in &input[1..] {
-        let chars = row.chars().collect::<Vec<_>>();
-        for (wheel, idx) in (0..chars.len()).step_by(4).enumerate() {
-            if chars[idx] != ' ' {
-                wheels[wheel]
-                    .sequence
-                    .push([chars[idx], chars[idx + 1], chars[idx + 2]]);
+    for row in rows {
+        for (wheel, face) in row.blocks(3, 1).enumerate() {
+            let Some(face) = face else {
+                continue;
+            };
+            let Some(wheel) = wheels.get_mut(wheel) else {
+                return Err(face.error("no more faces than wheels"));
+            };
+            let chars = face.as_str().chars().collect::<Vec<_>>();
+            let Ok(chars) = <[char; 3]>::try_from(chars) else {
+                return Err(face.error("3 characters"));
             };
+            wheel.sequence.push(chars);
         }
     }
-    wheels
+    Ok(wheels)
 }
 
 #[cfg(test)]
@@ -125,7 +117,7 @@ mod tests {
     #[test]
     fn test_one() {
         let expected = ">.- -.- ^,-";
-        let wheels = parse_input(read_lines(
+        let wheels = Source::new(
             "1,2,3
 
 ^_^ -.- ^,-
@@ -133,7 +125,9 @@ mod tests {
 -_- -.- >.<
     -.^ ^_^
     >.>",
-        ));
+        )
+        .parse_with(parse_input)
+        .unwrap();
         let actual = part_one(wheels, 100);
         assert_eq!(expected, actual);
     }
@@ -141,7 +135,7 @@ mod tests {
     #[test]
     fn test_two() {
         let expected = 280014668134;
-        let wheels = parse_input(read_lines(
+        let wheels = Source::new(
             "1,2,3
 
 ^_^ -.- ^,-
@@ -149,7 +143,9 @@ mod tests {
 -_- -.- >.<
     -.^ ^_^
     >.>",
-        ));
+        )
+        .parse_with(parse_input)
+        .unwrap();
         let actual = part_two(wheels, 202420242024);
         assert_eq!(expected, actual);
     }
@@ -157,7 +153,7 @@ mod tests {
     #[test]
     fn test_three() {
         let expected = "627 128";
-        let wheels = parse_input(read_lines(
+        let wheels = Source::new(
             "1,2,3
 
 ^_^ -.- ^,-
@@ -165,7 +161,9 @@ mod tests {
 -_- -.- ^.^
     -.^ >.<
     >.>",
-        ));
+        )
+        .parse_with(parse_input)
+        .unwrap();
         let actual = part_three(wheels, 256);
         assert_eq!(expected, actual);
     }
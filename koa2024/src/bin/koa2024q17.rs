@@ -1,10 +1,9 @@
-use std::{
-    cmp::Reverse,
-    collections::{BinaryHeap, HashSet},
-};
+use std::collections::HashSet;
 
+use ebclib::graph::mst::{Manhattan, prim};
 use puzlib::{Vec2D, read_grid_to_map};
 
+#[ebclib::quest(event = "koa", year = 2024, number = 17)]
 fn main() {
     let input = lines_to_stars(read_grid_to_map("koa2024/inputs/quest17.1.txt"));
     println!("Part 1: {}", part_one(input));
@@ -31,15 +30,13 @@ fn part_two(stars: HashSet<Vec2D<usize>>) -> usize {
     best_constellation(stars)
 }
 
-fn part_three(mut stars: HashSet<Vec2D<usize>>) -> usize {
-    let mut constellations = Vec::new();
-    while !stars.is_empty() {
-        let cons = find_brilliant_constellation(&stars, 6);
-        for star in cons.iter() {
-            stars.remove(star);
-        }
-        constellations.push(best_constellation(cons));
-    }
+fn part_three(stars: HashSet<Vec2D<usize>>) -> usize {
+    // Stars closer than 6 apart are in the same constellation.
+    let mut constellations = prim(&as_points(&stars), &Manhattan)
+        .clusters(5)
+        .iter()
+        .map(|cluster| cluster.nodes.len() + cluster.weight as usize)
+        .collect::<Vec<_>>();
     constellations.sort_by(|a, b| b.cmp(a));
     constellations
         .into_iter()
@@ -49,50 +46,14 @@ fn part_three(mut stars: HashSet<Vec2D<usize>>) -> usize {
 }
 
 fn best_constellation(stars: HashSet<Vec2D<usize>>) -> usize {
-    // Prim's Mimimum Spanning Tree
-    let mut total_distance = 0;
-    let mut visited = HashSet::new();
-    let mut to_visit = BinaryHeap::new();
-    let start = *stars.iter().min().unwrap();
-    to_visit.push(Reverse((0, start)));
-    while let Some(Reverse((dist, star))) = to_visit.pop() {
-        if visited.contains(&star) {
-            continue;
-        }
-        total_distance += dist;
-        visited.insert(star);
-        for other in stars.iter() {
-            if !visited.contains(other) {
-                to_visit.push(Reverse((star.manhattan(*other), *other)));
-            }
-        }
-    }
-
-    stars.len() + total_distance
+    stars.len() + prim(&as_points(&stars), &Manhattan).weight() as usize
 }
 
-fn find_brilliant_constellation(
-    stars: &HashSet<Vec2D<usize>>,
-    max_dist: usize,
-) -> HashSet<Vec2D<usize>> {
-    // Prim's Mimimum Spanning Tree
-    let mut constellation = HashSet::new();
-    let mut to_visit = BinaryHeap::new();
-    let start = *stars.iter().min().unwrap();
-    to_visit.push(Reverse((0, start)));
-    while let Some(Reverse((_, star))) = to_visit.pop() {
-        if constellation.contains(&star) {
-            continue;
-        }
-        constellation.insert(star);
-        for other in stars.iter() {
-            let dist = star.manhattan(*other);
-            if !constellation.contains(other) && dist < max_dist {
-                to_visit.push(Reverse((dist, *other)));
-            }
-        }
-    }
-    constellation
+fn as_points(stars: &HashSet<Vec2D<usize>>) -> Vec<[i64; 2]> {
+    stars
+        .iter()
+        .map(|star| [star.0 as i64, star.1 as i64])
+        .collect()
 }
 
 #[cfg(test)]
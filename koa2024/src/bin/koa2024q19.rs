@@ -1,31 +1,48 @@
 use std::collections::{HashMap, HashSet};
 
-use puzlib::read_lines;
+use ebclib::{
+    grid::Grid,
+    parse::{self, ParseError, Source},
+    render::{self, Animation, Image},
+    step::{self, Simulation},
+    symbol::Symbol,
+};
 
-fn main() {
-    let input = read_lines("koa2024/inputs/quest19.1.txt");
-    let instructions = parse_instructions(&input[0]);
-    let message = input[1..]
-        .iter()
-        .map(|line| line.chars().collect())
-        .collect();
+#[ebclib::quest(event = "koa", year = 2024, number = 19)]
+fn main() -> Result<(), ParseError> {
+    if let Some(part) = step::requested(&[1, 2, 3]) {
+        let path = format!("koa2024/inputs/quest19.{part}.txt");
+        let (instructions, message) = Source::read(&path)?.parse_with(parse_input)?;
+        let rounds = [1, 100, 1048576000][part as usize - 1];
+        step::run(Decoding {
+            instructions,
+            message,
+            rounds,
+        });
+    }
+
+    let (instructions, message) =
+        Source::read("koa2024/inputs/quest19.1.txt")?.parse_with(parse_input)?;
     println!("Part 1: {}", part_one(instructions, message));
 
-    let input = read_lines("koa2024/inputs/quest19.2.txt");
-    let instructions = parse_instructions(&input[0]);
-    let message = input[1..]
-        .iter()
-        .map(|line| line.chars().collect())
-        .collect();
+    let (instructions, message) =
+        Source::read("koa2024/inputs/quest19.2.txt")?.parse_with(parse_input)?;
     println!("Part 2: {}", part_two(instructions, message));
 
-    let input = read_lines("koa2024/inputs/quest19.3.txt");
-    let instructions = parse_instructions(&input[0]);
-    let message = input[1..]
+    let (instructions, message) =
+        Source::read("koa2024/inputs/quest19.3.txt")?.parse_with(parse_input)?;
+    println!("Part 3: {}", part_three(instructions, message));
+    Ok(())
+}
+
+/// A line of rotations, then the message.
+fn parse_input<S: AsRef<str>>(lines: &[S]) -> Result<(Vec<Rotation>, Vec<Vec<char>>), ParseError> {
+    let (header, message) = parse::headed(lines)?;
+    let message = message
         .iter()
-        .map(|line| line.chars().collect())
+        .map(|line| line.as_str().chars().collect())
         .collect();
-    println!("Part 3: {}", part_three(instructions, message));
+    Ok((header.trim().symbols()?, message))
 }
 
 fn part_one(instruction: Vec<Rotation>, mut message: Vec<Vec<char>>) -> String {
@@ -34,12 +51,33 @@ fn part_one(instruction: Vec<Rotation>, mut message: Vec<Vec<char>>) -> String {
 }
 
 fn part_two(instruction: Vec<Rotation>, mut message: Vec<Vec<char>>) -> String {
+    let mut animation = render::render_dir().map(|_| Animation::new());
     for _ in 0..100 {
+        if let Some(animation) = animation.as_mut() {
+            animation.push(picture(&message));
+        }
         rotate(&instruction, &mut message);
     }
+    if let (Some(dir), Some(animation)) = (render::render_dir(), animation)
+        && let Err(e) = animation.save_gif(dir.join("quest19.gif"), 10)
+    {
+        eprintln!("{e}");
+    }
     read_message(&message)
 }
 
+/// The message with its markers in red and the text dark, so the code can be seen
+/// coming together.
+fn picture(message: &[Vec<char>]) -> Image {
+    let grid = Grid::from_rows(message.to_vec());
+    Image::from_grid(&grid, 6, |ch| match ch {
+        '>' | '<' => [220, 40, 40],
+        '.' => render::WHITE,
+        ch if ch.is_ascii_alphanumeric() => [30, 30, 60],
+        _ => [170, 170, 170],
+    })
+}
+
 fn part_three(instruction: Vec<Rotation>, message: Vec<Vec<char>>) -> String {
     let mut step = (0..message.len())
         .map(|row| {
@@ -87,6 +125,35 @@ fn part_three(instruction: Vec<Rotation>, message: Vec<Vec<char>>) -> String {
     read_message(&new_message)
 }
 
+/// The message rotated a full round of instructions a tick.
+struct Decoding {
+    instructions: Vec<Rotation>,
+    message: Vec<Vec<char>>,
+    rounds: usize,
+}
+
+impl Simulation for Decoding {
+    /// The rounds done and the message.
+    type State = (usize, Vec<Vec<char>>);
+
+    fn state(&self) -> Self::State {
+        (0, self.message.clone())
+    }
+
+    fn step(&self, (round, message): &Self::State) -> Option<Self::State> {
+        if *round == self.rounds {
+            return None;
+        }
+        let mut message = message.clone();
+        rotate(&self.instructions, &mut message);
+        Some((round + 1, message))
+    }
+
+    fn render(&self, (_, message): &Self::State) -> Grid<char> {
+        Grid::from_rows(message.clone())
+    }
+}
+
 const ROT_CW: [(i64, i64); 8] = [
     (-1, -1),
     (0, -1),
@@ -148,20 +215,11 @@ fn read_message(message: &[Vec<char>]) -> String {
     res
 }
 
-fn parse_instructions(instruction: &str) -> Vec<Rotation> {
-    instruction
-        .chars()
-        .filter_map(|ch| match ch {
-            'R' => Some(Rotation::Clockwise),
-            'L' => Some(Rotation::CounterClockwise),
-            _ => None,
-        })
-        .collect()
-}
-
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Symbol)]
 enum Rotation {
+    #[symbol('R')]
     Clockwise,
+    #[symbol('L')]
     CounterClockwise,
 }
 
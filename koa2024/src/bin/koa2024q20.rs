@@ -1,161 +1,226 @@
 use std::collections::HashMap;
 
+use ebclib::{
+    dp::frontier::{Frontier, Merge},
+    grid::Grid,
+    render::{self, Animation, Image},
+    step::{self, Simulation},
+    symbol::{self, Symbol},
+};
 use puzlib::{Dir, Vec2D, read_grid_to_map};
 
-fn main() {
-    let input = read_grid_to_map("koa2024/inputs/quest20.1.txt");
-    println!("Part 1: {}", part_one(input));
+#[ebclib::quest(event = "koa", year = 2024, number = 20)]
+fn main() -> Result<(), String> {
+    if let Some(part) = step::requested(&[1, 2]) {
+        let path = format!("koa2024/inputs/quest20.{part}.txt");
+        let map = Map::new(read_grid_to_map(&path))?;
+        step::run(Flight { map, part });
+    }
+
+    let map = Map::new(read_grid_to_map("koa2024/inputs/quest20.1.txt"))?;
+    println!("Part 1: {}", part_one(map));
 
-    let input = read_grid_to_map("koa2024/inputs/quest20.2.txt");
-    println!("Part 2: {}", part_two(input));
+    let map = Map::new(read_grid_to_map("koa2024/inputs/quest20.2.txt"))?;
+    println!("Part 2: {}", part_two(map));
 
-    let input = read_grid_to_map("koa2024/inputs/quest20.3.txt");
-    println!("Part 3: {}", part_three(input, 384400));
+    let map = Map::new(read_grid_to_map("koa2024/inputs/quest20.3.txt"))?;
+    println!("Part 3: {}", part_three(map, 384400));
+    Ok(())
 }
 
-fn part_one(input: Vec<((usize, usize), char)>) -> i64 {
-    let map = Map::new(input);
-    let mut step: HashMap<Glider, i64> = HashMap::from_iter(
-        Dir::cardinals_unchecked(&Vec2D(0, 0))
-            .iter()
-            .map(|heading| (Glider::new(map.start, *heading), 1_000)),
+fn part_one(map: Map) -> i64 {
+    let mut frontier = Frontier::new(
+        map.launch(1_000),
+        Merge::Max,
+        |glider: &Glider, altitude| glider.moves(altitude, &map),
     );
+    frontier.run(100);
+    frontier.best().unwrap()
+}
 
-    let mut time = 0;
-    while time < 100 {
-        let mut next_step = step.clone();
-        for (glider, altitude) in step {
-            for (state, mut new_altitude) in glider.moves(altitude, &map) {
-                let cur_alt = next_step.entry(state).or_insert(new_altitude);
-                *cur_alt = *cur_alt.max(&mut new_altitude);
+fn part_two(map: Map) -> usize {
+    let mut frontier = Frontier::new(
+        map.launch(10_000),
+        Merge::Max,
+        |glider: &Glider, altitude| glider.moves(altitude, &map),
+    );
+    let backdrop = render::render_dir().map(|_| backdrop(&map));
+    let mut animation = Animation::new();
+    let mut time = usize::MAX;
+    while !frontier.is_empty() {
+        if let Some(backdrop) = &backdrop {
+            let mut frame = backdrop.clone();
+            for (glider, _) in frontier.iter() {
+                mark(&mut frame, glider.pos);
             }
+            animation.push(frame);
+        }
+        if frontier
+            .iter()
+            .any(|(glider, altitude)| altitude >= 10_000 && glider.returned(&map))
+        {
+            time = frontier.time();
+            break;
         }
-        step = next_step;
-        time += 1;
+        frontier.step();
     }
-    *step.values().max().unwrap()
+    if let Some(dir) = render::render_dir()
+        && let Err(e) = animation.save_gif(dir.join("quest20.gif"), 5)
+    {
+        eprintln!("{e}");
+    }
+    time
 }
 
-fn part_two(input: Vec<((usize, usize), char)>) -> usize {
-    let map = Map::new(input);
-    let mut step: HashMap<Glider, i64> = HashMap::from_iter(
-        Dir::cardinals_unchecked(&Vec2D(0, 0))
-            .iter()
-            .map(|heading| (Glider::new(map.start, *heading), 10_000)),
-    );
-    let mut time = 0;
-    while !step.is_empty() {
-        let mut next_step = step.clone();
-        for (glider, altitude) in step {
-            if altitude >= 10_000 && glider.returned(&map) {
-                return time;
-            }
-            for (state, mut new_altitude) in glider.moves(altitude, &map) {
-                let cur_alt = next_step.entry(state).or_insert(new_altitude);
-                *cur_alt = *cur_alt.max(&mut new_altitude);
-            }
-        }
-        step = next_step;
-        time += 1;
+const SCALE: usize = 3;
+
+/// The map with warm air orange, cold air blue and obstacles grey.
+fn backdrop(map: &Map) -> Image {
+    let (rows, cols) = map.size();
+    let mut grid = Grid::new(rows, cols, Segment::Obstacle);
+    for (pos, segment) in map.grid.iter() {
+        grid[(pos.0 as usize, pos.1 as usize)] = *segment;
     }
-    usize::MAX
+    Image::from_grid(&grid, SCALE, |segment| match segment {
+        Segment::None => render::WHITE,
+        Segment::Warm => [250, 190, 140],
+        Segment::Cold => [150, 200, 250],
+        Segment::Obstacle => [70, 70, 70],
+    })
 }
 
-fn part_three(input: Vec<((usize, usize), char)>, altitude: i64) -> i64 {
-    let map = Map::new(input);
-    let mut furthest_reach: Option<i64> = None;
-    let mut best_col = None;
-    let mut min_drop = i64::MIN;
-    let Vec2D(rows, cols) = *map.grid.keys().max().unwrap() + Vec2D(1, 1);
-    let mut best_offset = cols as i64;
-    'outer: for col in 0..cols {
-        let mut alt = 0;
-        for row in 0..rows {
-            if let Some((_, delta)) = map.get(&Vec2D(row, col)) {
-                alt += delta;
-            } else {
-                continue 'outer;
-            }
-        }
-        let lateral_offset = col.abs_diff(map.start.1) as i64;
-        if alt > min_drop || (alt == min_drop && lateral_offset < best_offset) {
-            min_drop = alt;
-            best_offset = lateral_offset;
-            best_col = Some(col);
+/// Paint a glider's cell red.
+fn mark(image: &mut Image, pos: Vec2D<i64>) {
+    let (x, y) = (pos.1 as usize * SCALE, pos.0 as usize * SCALE);
+    for dy in 0..SCALE {
+        for dx in 0..SCALE {
+            image.set(x + dx, y + dy, [220, 40, 40]);
         }
     }
+}
 
-    let Some(best_col) = best_col else {
-        panic!("Can't fly down a column")
+fn part_three(map: Map, altitude: i64) -> i64 {
+    let map = map.repeating();
+    let rows = map.rows.unwrap();
+    let cols = map.grid.keys().map(|pos| pos.1 + 1).max().unwrap();
+    let glide = |glider: &Glider, altitude| glider.glides(altitude, &map);
+    // Only gliders in the same column heading the same way are compared. Anywhere in the
+    // next map over can be reached losing at most 2 per step, so a glider with that much
+    // more altitude can follow the other. Failing that, one a whole number of maps
+    // further south in the same spot with no less altitude can.
+    let spare = 2 * (cols + 2 * rows);
+    let mut frontier = Frontier::new(map.launch(altitude), Merge::Max, glide).dominance(
+        |glider| (glider.pos.1, glider.heading),
+        move |(a, a_alt), (b, b_alt)| {
+            let behind = (b.pos.0 - a.pos.0).max(0);
+            let same_spot = a.pos.0.rem_euclid(rows) == b.pos.0.rem_euclid(rows);
+            a_alt >= b_alt + spare + 2 * behind
+                || (same_spot && a.pos.0 >= b.pos.0 && a_alt >= b_alt)
+        },
+    );
+    let canonical = |glider: &Glider| {
+        let pos = Vec2D(glider.pos.0.rem_euclid(rows), glider.pos.1);
+        (Glider { pos, ..*glider }, glider.pos.0.div_euclid(rows))
     };
+    let period = frontier
+        .find_period(canonical, 100 * rows as usize)
+        .expect("Glider never settles down");
+    assert!(period.change < 0, "Glider never lands");
+    // Skip ahead while every glider stays a couple of repeats clear of the ground.
+    let lowest = frontier.iter().map(|(_, altitude)| altitude).min().unwrap();
+    let periods = (lowest / -period.change - 2).max(0) as usize;
+    frontier.skip_periods(&period, periods, |glider, shift| Glider {
+        pos: glider.pos + Vec2D(shift * rows, 0),
+        ..*glider
+    });
 
-    let mut step = HashMap::<Glider, i64>::from([
-        (Glider::new(map.start, Vec2D(1, 0)), altitude),
-        (Glider::new(map.start, Vec2D(0, 1)), altitude),
-        (Glider::new(map.start, Vec2D(0, -1)), altitude),
-    ]);
-
-    // Move to the ideal column. Will be at the bottom of the known grid.
-    while !step.is_empty() {
-        let mut next_step = HashMap::new();
-        for (glider, alt) in step.iter() {
-            if alt == &0 {
-                furthest_reach = match furthest_reach {
-                    Some(reach) => Some(reach.max(glider.pos.0)),
-                    None => Some(glider.pos.0),
-                };
-            }
-            for (state, new_altitude) in glider.moves(*alt, &map) {
-                if new_altitude < 0 {
-                    continue;
-                }
-                if state.pos.1 <= glider.pos.1 && glider.pos.1 < best_col {
-                    continue;
-                }
-                if state.pos.1 >= glider.pos.1 && glider.pos.1 > best_col {
-                    continue;
-                }
-                if let Some(cur_alt) = step.get(&state) {
-                    next_step.insert(state, *cur_alt.max(&new_altitude));
-                } else {
-                    next_step.insert(state, new_altitude);
-                }
-            }
-        }
-        step = next_step;
+    let mut furthest = 0;
+    while !frontier.is_empty() {
+        furthest = frontier
+            .iter()
+            .filter(|(_, altitude)| *altitude == 0)
+            .map(|(glider, _)| glider.pos.0)
+            .fold(furthest, i64::max);
+        frontier.step();
+    }
+    furthest
+}
+
+/// Every glider still in the air, a move a tick, pointing the way it's heading.
+struct Flight {
+    map: Map,
+    part: u8,
+}
+
+impl Simulation for Flight {
+    /// The time and every glider with its altitude.
+    type State = (usize, Vec<(Glider, i64)>);
+
+    fn state(&self) -> Self::State {
+        let altitude = if self.part == 1 { 1_000 } else { 10_000 };
+        (0, self.map.launch(altitude))
     }
 
-    // If we hit 0 on the first pass, return that.
-    if let Some(dist) = furthest_reach {
-        return dist;
+    fn step(&self, (time, gliders): &Self::State) -> Option<Self::State> {
+        let done = match self.part {
+            1 => *time == 100,
+            _ => gliders
+                .iter()
+                .any(|(glider, altitude)| *altitude >= 10_000 && glider.returned(&self.map)),
+        };
+        if done || gliders.is_empty() {
+            return None;
+        }
+        let mut frontier =
+            Frontier::new(gliders.iter().copied(), Merge::Max, |glider, altitude| {
+                glider.moves(altitude, &self.map)
+            });
+        frontier.step();
+        let mut gliders = frontier
+            .iter()
+            .map(|(glider, altitude)| (*glider, altitude))
+            .collect::<Vec<_>>();
+        gliders.sort();
+        Some((time + 1, gliders))
     }
-    let altitude = altitude - best_offset - 1;
-    let grids_traversed = altitude / min_drop.abs();
-    let mut distance = grids_traversed * rows;
-    let mut cur_altitude = altitude - (grids_traversed * min_drop.abs());
-    let mut row = 1;
-    distance += 1;
-    while cur_altitude > 0 {
-        if let Some((_, delta)) = map.get(&Vec2D(row, best_col)) {
-            cur_altitude += delta;
-            distance += 1;
-            row += 1;
+
+    fn render(&self, (_, gliders): &Self::State) -> Grid<char> {
+        let (rows, cols) = self.map.size();
+        let mut grid = Grid::new(rows, cols, '#');
+        for (pos, segment) in self.map.grid.iter() {
+            grid[(pos.0 as usize, pos.1 as usize)] = char::from(*segment);
         }
+        for (glider, _) in gliders {
+            grid[(glider.pos.0 as usize, glider.pos.1 as usize)] = match glider.heading {
+                Vec2D(-1, 0) => '^',
+                Vec2D(1, 0) => 'v',
+                Vec2D(0, -1) => '<',
+                _ => '>',
+            };
+        }
+        let best = gliders.iter().map(|(_, altitude)| altitude).max();
+        let mut lines = vec![
+            format!("Best altitude {}", best.unwrap_or(&0)),
+            String::new(),
+        ];
+        lines.extend((0..rows).map(|row| (0..cols).map(|col| grid[(row, col)]).collect()));
+        Grid::parse_padded(&lines, ' ')
     }
-    distance
 }
 
 #[derive(Debug, Clone, Default)]
 struct Map {
     grid: HashMap<Vec2D<i64>, Segment>,
     start: Vec2D<i64>,
+    /// Rows before the map repeats, if it goes on south forever.
+    rows: Option<i64>,
     checkpoint_a: Option<Vec2D<i64>>,
     checkpoint_b: Option<Vec2D<i64>>,
     checkpoint_c: Option<Vec2D<i64>>,
 }
 
 impl Map {
-    fn new(input: Vec<((usize, usize), char)>) -> Self {
+    fn new(input: Vec<((usize, usize), char)>) -> Result<Self, String> {
         let mut map = Self::default();
         for (node, ch) in input {
             if ch == 'S' {
@@ -170,14 +235,40 @@ impl Map {
             if ch == 'C' {
                 map.checkpoint_c = Some(Vec2D(node.0 as i64, node.1 as i64));
             }
+            let segment = symbol::parse_at(ch, node.0, node.1)?;
             map.grid
-                .insert(Vec2D(node.0 as i64, node.1 as i64), ch.into());
+                .insert(Vec2D(node.0 as i64, node.1 as i64), segment);
         }
-        map
+        Ok(map)
+    }
+
+    /// Rows and columns of a map that doesn't repeat.
+    fn size(&self) -> (usize, usize) {
+        let rows = self.grid.keys().map(|pos| pos.0 + 1).max().unwrap_or(0);
+        let cols = self.grid.keys().map(|pos| pos.1 + 1).max().unwrap_or(0);
+        (rows as usize, cols as usize)
+    }
+
+    /// A glider at the start facing each way.
+    fn launch(&self, altitude: i64) -> Vec<(Glider, i64)> {
+        Dir::cardinals_unchecked(&Vec2D(0, 0))
+            .iter()
+            .map(|heading| (Glider::new(self.start, *heading), altitude))
+            .collect()
+    }
+
+    /// The same map repeated south forever.
+    fn repeating(mut self) -> Self {
+        self.rows = self.grid.keys().map(|pos| pos.0 + 1).max();
+        self
     }
 
     fn get(&self, pos: &Vec2D<i64>) -> Option<(Segment, i64)> {
-        if let Some(segment) = self.grid.get(pos)
+        let pos = match self.rows {
+            Some(rows) if pos.0 >= 0 => Vec2D(pos.0 % rows, pos.1),
+            _ => *pos,
+        };
+        if let Some(segment) = self.grid.get(&pos)
             && let Some(delta) = segment.delta_altitude()
         {
             Some((*segment, delta))
@@ -262,16 +353,31 @@ impl Glider {
         res
     }
 
+    /// Moves towards the furthest landing. Landed gliders stay put, and heading back
+    /// north never helps.
+    fn glides(&self, altitude: i64, map: &Map) -> Vec<(Self, i64)> {
+        if altitude == 0 {
+            return vec![];
+        }
+        let mut moves = self.moves(altitude, map);
+        moves.retain(|(glider, altitude)| *altitude >= 0 && glider.heading != Vec2D(-1, 0));
+        moves
+    }
+
     fn returned(&self, map: &Map) -> bool {
         self.pos == map.start && self.checkpoints == 3
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Symbol)]
 enum Segment {
+    #[symbol('.', 'S', 'A', 'B', 'C')]
     None,
+    #[symbol('+')]
     Warm,
+    #[symbol('-')]
     Cold,
+    #[symbol('#')]
     Obstacle,
 }
 impl Segment {
@@ -285,18 +391,6 @@ impl Segment {
     }
 }
 
-impl From<char> for Segment {
-    fn from(value: char) -> Self {
-        match value {
-            '.' | 'S' | 'A' | 'B' | 'C' => Self::None,
-            '+' => Self::Warm,
-            '-' => Self::Cold,
-            '#' => Self::Obstacle,
-            _ => unreachable!(),
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -304,7 +398,7 @@ mod tests {
     #[test]
     fn test_one() {
         let expected = 1045;
-        let input = read_grid_to_map(
+        let map = Map::new(read_grid_to_map(
             "#....S....#
 #.........#
 #---------#
@@ -312,15 +406,16 @@ mod tests {
 #..+.+.+..#
 #.+-.+.++.#
 #.........#",
-        );
-        let actual = part_one(input);
+        ))
+        .unwrap();
+        let actual = part_one(map);
         assert_eq!(expected, actual);
     }
 
     #[test]
     fn test_two() {
         let expected = 24;
-        let input = read_grid_to_map(
+        let map = Map::new(read_grid_to_map(
             "####S####
 #-.+++.-#
 #.+.+.+.#
@@ -329,15 +424,16 @@ mod tests {
 #.+-.-+.#
 #.+.B.+.#
 #########",
-        );
-        let actual = part_two(input);
+        ))
+        .unwrap();
+        let actual = part_two(map);
         assert_eq!(expected, actual);
     }
 
     #[test]
     fn test_three() {
         let expected = 768790;
-        let input = read_grid_to_map(
+        let map = Map::new(read_grid_to_map(
             "#......S......#
 #-...+...-...+#
 #.............#
@@ -346,8 +442,35 @@ mod tests {
 #-...-...+...-#
 #.............#
 #..#...+...+..#",
-        );
-        let actual = part_three(input, 384400);
+        ))
+        .unwrap();
+        let actual = part_three(map, 384400);
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn test_three_matches_stepping() {
+        let map = Map::new(read_grid_to_map(
+            "#...S..#
+#-..+..#
+#...-.+#
+#.+....#",
+        ))
+        .unwrap();
+        // Every glider stepped until all have landed, with nothing pruned or skipped.
+        let repeating = map.clone().repeating();
+        let mut frontier = Frontier::new(repeating.launch(100), Merge::Max, |glider, altitude| {
+            glider.glides(altitude, &repeating)
+        });
+        let mut furthest = 0;
+        while !frontier.is_empty() {
+            furthest = frontier
+                .iter()
+                .filter(|(_, altitude)| *altitude == 0)
+                .map(|(glider, _)| glider.pos.0)
+                .fold(furthest, i64::max);
+            frontier.step();
+        }
+        assert_eq!(furthest, part_three(map, 100));
+    }
 }
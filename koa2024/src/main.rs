@@ -1,21 +1,111 @@
+use ebclib::{par::THREADS_ENV, schema};
+
+include!(concat!(env!("OUT_DIR"), "/quests.rs"));
+
 fn main() {
+    let mut args = std::env::args().skip(1).peekable();
+    if args.peek().is_some_and(|arg| arg == "list") {
+        for quest in QUESTS {
+            println!("{quest}: {}", quest.bin());
+        }
+        return;
+    }
+    if args.peek().is_some_and(|arg| arg == "check") {
+        let failed = QUESTS
+            .iter()
+            .filter(|quest| match schema::check_quest(quest) {
+                Ok(Some(checked)) => {
+                    println!("{quest}: {checked} inputs ok");
+                    false
+                }
+                Ok(None) => {
+                    println!("{quest}: unchecked, no input spec");
+                    false
+                }
+                Err(report) => {
+                    println!("{quest}:\n{report}");
+                    true
+                }
+            })
+            .count();
+        std::process::exit(if failed == 0 { 0 } else { 1 });
+    }
+    if args.peek().is_some_and(|arg| arg == "step") {
+        args.next();
+        let quest = args
+            .next()
+            .and_then(|number| number.parse().ok())
+            .and_then(|number: u8| QUESTS.iter().find(|quest| quest.number == number));
+        let (Some(quest), Some(part)) = (quest, args.next()) else {
+            eprintln!("Usage: step <quest> <part>");
+            std::process::exit(2);
+        };
+        // The quest draws to and reads from this terminal itself.
+        let status = std::process::Command::new("cargo")
+            .args(["run", "--release", "-q", "--bin", &quest.bin(), "--"])
+            .args(["step", &part])
+            .status();
+        std::process::exit(status.ok().and_then(|status| status.code()).unwrap_or(1));
+    }
+    let threads = match threads_flag(args) {
+        Ok(threads) => threads,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    };
     let mut res = vec![];
-    for day in 1..=12 {
-        if let Ok(out) = std::process::Command::new("cargo")
-            .args(["run", "--bin", &format!("ebc2024q{day:02}")])
-            .output()
-        {
-            res.push(format!("2024 Quest {day:02}").as_bytes().to_vec());
+    for quest in QUESTS {
+        // Inputs that don't match their spec would only fail part way through solving.
+        if let Err(report) = schema::check_quest(quest) {
+            res.push(format!("{quest}\n{report}").as_bytes().to_vec());
+            continue;
+        }
+        let mut command = std::process::Command::new("cargo");
+        command.args(["run", "--release", "--bin", &quest.bin()]);
+        if let Some(threads) = threads {
+            command.env(THREADS_ENV, threads.to_string());
+        }
+        if let Ok(out) = command.output() {
+            res.push(quest.to_string().as_bytes().to_vec());
             res.push(out.stdout);
+            // A quest that fails says why on stderr, e.g. a parse error with the bad line.
+            if !out.status.success() {
+                res.push(out.stderr);
+            }
         } else {
-            res.push(
-                format!("Failed to run 2024 quest {day:02}")
-                    .as_bytes()
-                    .to_vec(),
-            );
+            res.push(format!("Failed to run {quest}").as_bytes().to_vec());
         };
     }
     for day in res.iter() {
         println!("{}", str::from_utf8(day).unwrap());
     }
 }
+
+/// Worker thread count from `--threads N` or `--threads=N`, passed on to every quest.
+fn threads_flag(mut args: impl Iterator<Item = String>) -> Result<Option<usize>, String> {
+    let mut threads = None;
+    while let Some(arg) = args.next() {
+        let value = match arg.strip_prefix("--threads") {
+            Some("") => args.next(),
+            Some(value) if value.starts_with('=') => Some(value[1..].to_string()),
+            _ => return Err(format!("Unknown argument {arg}")),
+        };
+        let Some(count) = value.and_then(|v| v.parse().ok()).filter(|n| *n > 0) else {
+            return Err("--threads needs a positive number".to_string());
+        };
+        threads = Some(count);
+    }
+    Ok(threads)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index() {
+        assert!(QUESTS.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(QUESTS.iter().any(|quest| quest.bin() == "koa2024q07"));
+    }
+}